@@ -0,0 +1,17 @@
+-- Store the board dimensions on the room
+ALTER TABLE rooms ADD COLUMN board_rows INTEGER NOT NULL DEFAULT 5;
+ALTER TABLE rooms ADD COLUMN board_cols INTEGER NOT NULL DEFAULT 5;
+
+-- Store the grid position of every field
+ALTER TABLE fields ADD COLUMN row_index INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE fields ADD COLUMN col_index INTEGER NOT NULL DEFAULT 0;
+
+-- Lay out existing boards row by row in insertion order
+UPDATE fields
+SET row_index = numbered.n / 5,
+    col_index = numbered.n % 5
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY room_id ORDER BY id) - 1 AS n
+    FROM fields
+) AS numbered
+WHERE fields.id = numbered.id;
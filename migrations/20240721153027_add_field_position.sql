@@ -0,0 +1,9 @@
+-- Store the flat board position of every field
+ALTER TABLE fields ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+UPDATE fields
+SET position = fields.row_index * rooms.board_cols + fields.col_index
+FROM rooms
+WHERE fields.room_id = rooms.id;
+
+CREATE INDEX idx_fields_room_id_position ON fields (room_id, position);
@@ -0,0 +1,32 @@
+-- Create the games table, one row per round played in a room
+CREATE TABLE games (
+    id SERIAL PRIMARY KEY,
+    room_id INTEGER NOT NULL,
+    starting_team VARCHAR(50) NOT NULL DEFAULT 'red',
+    finished_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_room
+    FOREIGN KEY (room_id)
+    REFERENCES rooms (id)
+    ON DELETE CASCADE
+);
+
+-- Every existing room gets its board wrapped in a first game
+INSERT INTO games (room_id, created_at)
+SELECT id, created_at FROM rooms;
+
+ALTER TABLE fields ADD COLUMN game_id INTEGER;
+
+UPDATE fields
+SET game_id = games.id
+FROM games
+WHERE fields.room_id = games.room_id;
+
+ALTER TABLE fields ALTER COLUMN game_id SET NOT NULL;
+ALTER TABLE fields
+    ADD CONSTRAINT fk_game
+    FOREIGN KEY (game_id)
+    REFERENCES games (id)
+    ON DELETE CASCADE;
+
+CREATE INDEX idx_fields_game_id_position ON fields (game_id, position);
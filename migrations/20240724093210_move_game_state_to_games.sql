@@ -0,0 +1,49 @@
+-- Move the per-round state from rooms onto games
+ALTER TABLE games ADD COLUMN game_stage VARCHAR(50) NOT NULL DEFAULT 'waiting_for_players';
+ALTER TABLE games ADD COLUMN current_team VARCHAR(50) NOT NULL DEFAULT 'red';
+ALTER TABLE games ADD COLUMN winner VARCHAR(50);
+ALTER TABLE games ADD COLUMN board_rows INTEGER NOT NULL DEFAULT 5;
+ALTER TABLE games ADD COLUMN board_cols INTEGER NOT NULL DEFAULT 5;
+
+UPDATE games
+SET board_rows = rooms.board_rows,
+    board_cols = rooms.board_cols
+FROM rooms
+WHERE games.room_id = rooms.id;
+
+-- The latest game of each room carries the room's current state
+UPDATE games
+SET game_stage = rooms.game_stage,
+    current_team = rooms.current_team
+FROM rooms
+WHERE games.room_id = rooms.id
+AND games.id = (SELECT MAX(id) FROM games AS latest WHERE latest.room_id = rooms.id);
+
+-- Games archived by a rematch are over
+UPDATE games SET game_stage = 'finished' WHERE finished_at IS NOT NULL;
+
+ALTER TABLE rooms DROP COLUMN game_stage;
+ALTER TABLE rooms DROP COLUMN current_team;
+
+-- Fields now belong to a game only
+DROP INDEX idx_fields_room_id_position;
+ALTER TABLE fields DROP COLUMN room_id;
+
+-- Create the clues table
+CREATE TABLE clues (
+    id SERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    team VARCHAR(50) NOT NULL,
+    word VARCHAR(255) NOT NULL,
+    number INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_game
+    FOREIGN KEY (game_id)
+    REFERENCES games (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_player
+    FOREIGN KEY (player_id)
+    REFERENCES players (id)
+    ON DELETE CASCADE
+);
@@ -0,0 +1,26 @@
+-- How a won game is scored in the room tally
+ALTER TABLE rooms ADD COLUMN scoring_mode VARCHAR(50) NOT NULL DEFAULT 'wins';
+
+-- Points awarded to the winner of a game
+ALTER TABLE games ADD COLUMN points INTEGER NOT NULL DEFAULT 0;
+
+UPDATE games SET points = 1 WHERE winner IS NOT NULL;
+
+-- Create the player_results table, one row per player per finished game
+CREATE TABLE player_results (
+    id SERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    team VARCHAR(50) NOT NULL,
+    won BOOLEAN NOT NULL,
+    points INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_game
+    FOREIGN KEY (game_id)
+    REFERENCES games (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_player
+    FOREIGN KEY (player_id)
+    REFERENCES players (id)
+    ON DELETE CASCADE
+);
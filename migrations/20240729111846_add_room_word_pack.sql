@@ -0,0 +1,2 @@
+-- Word pack the room draws its boards from
+ALTER TABLE rooms ADD COLUMN word_pack VARCHAR(255) NOT NULL DEFAULT 'polski';
@@ -0,0 +1,14 @@
+-- Percentage of each board drawn from the room's custom words
+ALTER TABLE rooms ADD COLUMN custom_word_ratio INTEGER NOT NULL DEFAULT 100;
+
+-- Create the room_custom_words table
+CREATE TABLE room_custom_words (
+    id SERIAL PRIMARY KEY,
+    room_id INTEGER NOT NULL,
+    text VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_room
+    FOREIGN KEY (room_id)
+    REFERENCES rooms (id)
+    ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Language of the room's words and server messages
+ALTER TABLE rooms ADD COLUMN language VARCHAR(10) NOT NULL DEFAULT 'pl';
+
+CREATE INDEX idx_rooms_language ON rooms (language);
@@ -0,0 +1,31 @@
+-- Create the word_packs table for community word packs
+CREATE TABLE word_packs (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    language VARCHAR(10) NOT NULL,
+    tags VARCHAR(255)[] NOT NULL DEFAULT '{}',
+    author VARCHAR(255) NOT NULL,
+    edit_token VARCHAR(64) NOT NULL,
+    status VARCHAR(50) NOT NULL DEFAULT 'draft',
+    forked_from INTEGER,
+    usage_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_forked_from
+    FOREIGN KEY (forked_from)
+    REFERENCES word_packs (id)
+    ON DELETE SET NULL
+);
+
+CREATE INDEX idx_word_packs_status_usage_count ON word_packs (status, usage_count DESC);
+
+-- Create the word_pack_words table
+CREATE TABLE word_pack_words (
+    id SERIAL PRIMARY KEY,
+    word_pack_id INTEGER NOT NULL,
+    text VARCHAR(255) NOT NULL,
+    CONSTRAINT fk_word_pack
+    FOREIGN KEY (word_pack_id)
+    REFERENCES word_packs (id)
+    ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- Seed the board was generated from; games created before seeding have none
+ALTER TABLE games ADD COLUMN seed BIGINT;
@@ -0,0 +1,2 @@
+-- How many of the room's previous boards new boards avoid repeating words from
+ALTER TABLE rooms ADD COLUMN avoid_recent_games INT NOT NULL DEFAULT 3;
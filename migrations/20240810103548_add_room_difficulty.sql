@@ -0,0 +1,2 @@
+-- Mix of easy, normal and hard words the room's boards are drawn with
+ALTER TABLE rooms ADD COLUMN difficulty VARCHAR(10) NOT NULL DEFAULT 'normal';
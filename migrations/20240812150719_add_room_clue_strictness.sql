@@ -0,0 +1,2 @@
+-- How closely clues may resemble unrevealed board words
+ALTER TABLE rooms ADD COLUMN clue_strictness VARCHAR(10) NOT NULL DEFAULT 'strict';
@@ -0,0 +1,16 @@
+-- Whether the current team is waiting for a clue or guessing, and how many
+-- guesses it has left (NULL when unlimited)
+ALTER TABLE games ADD COLUMN turn_phase VARCHAR(50) NOT NULL DEFAULT 'clue';
+ALTER TABLE games ADD COLUMN guesses_remaining INTEGER;
+
+-- Teams that already have a clue in a running game keep guessing, without a limit
+UPDATE games
+SET turn_phase = 'guess'
+WHERE game_stage = 'in_progress'
+AND current_team = (SELECT team FROM clues WHERE clues.game_id = games.id ORDER BY id DESC LIMIT 1);
+
+ALTER TABLE clues ADD COLUMN unlimited BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- What a clue of 0 allows and whether showers may give unlimited clues
+ALTER TABLE rooms ADD COLUMN zero_clue_rule VARCHAR(10) NOT NULL DEFAULT 'unlimited';
+ALTER TABLE rooms ADD COLUMN allow_unlimited_clues BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,5 @@
+-- Seconds each turn may take, 0 when the room plays without a timer
+ALTER TABLE rooms ADD COLUMN turn_seconds INTEGER NOT NULL DEFAULT 0;
+
+-- When the current turn runs out, kept so timers survive a restart
+ALTER TABLE games ADD COLUMN turn_deadline TIMESTAMP;
@@ -0,0 +1,18 @@
+-- Seconds each team gets for the whole game in chess clock mode, 0 when off
+ALTER TABLE rooms ADD COLUMN time_bank_seconds INTEGER NOT NULL DEFAULT 0;
+
+-- Time each team has left; the running team's bank is only written back when
+-- its turn ends, until then games.turn_deadline is when it runs out
+CREATE TABLE time_banks (
+    id SERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL,
+    team VARCHAR(50) NOT NULL,
+    remaining_ms BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_game
+    FOREIGN KEY (game_id)
+    REFERENCES games (id)
+    ON DELETE CASCADE,
+    CONSTRAINT uq_time_banks_game_team
+    UNIQUE (game_id, team)
+);
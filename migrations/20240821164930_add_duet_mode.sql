@@ -0,0 +1,11 @@
+-- Classic two-team play or the cooperative Duet variant
+ALTER TABLE rooms ADD COLUMN game_mode VARCHAR(10) NOT NULL DEFAULT 'classic';
+
+-- Duet boards have a key per side: `team` is the red side's color of a card
+-- and `team_b` the blue side's. `neutral_for` is the side whose clue already
+-- turned the card up as a bystander, it stays open for the other side.
+ALTER TABLE fields ADD COLUMN team_b VARCHAR(50);
+ALTER TABLE fields ADD COLUMN neutral_for VARCHAR(50);
+
+-- Turns a Duet game has left, NULL when there is no limit
+ALTER TABLE games ADD COLUMN turns_remaining INTEGER;
@@ -0,0 +1,13 @@
+-- How many teams play in the room's games, two or three
+ALTER TABLE rooms ADD COLUMN team_count INTEGER NOT NULL DEFAULT 2;
+
+-- A game's teams in turn order, starting team first, and the teams that are
+-- out of it after hitting an assassin
+ALTER TABLE games ADD COLUMN teams VARCHAR(50)[] NOT NULL DEFAULT '{}';
+ALTER TABLE games ADD COLUMN eliminated_teams VARCHAR(50)[] NOT NULL DEFAULT '{}';
+
+UPDATE games
+SET teams = CASE starting_team
+    WHEN 'red' THEN ARRAY['red', 'blue']::VARCHAR(50)[]
+    ELSE ARRAY['blue', 'red']::VARCHAR(50)[]
+END;
@@ -0,0 +1,8 @@
+-- Whether spectators get to see the whole key, and how long after the game
+-- starts they do
+ALTER TABLE rooms ADD COLUMN spectators_see_key BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE rooms ADD COLUMN spectator_key_delay_seconds INTEGER NOT NULL DEFAULT 0;
+
+-- When the first clue was given
+ALTER TABLE games ADD COLUMN started_at TIMESTAMP;
+UPDATE games SET started_at = created_at WHERE game_stage != 'waiting_for_players';
@@ -0,0 +1,28 @@
+-- Whether a guesser's click reveals a card right away or counts as a vote,
+-- and the share of the guessers that has to agree on a card
+ALTER TABLE rooms ADD COLUMN reveal_mode VARCHAR(10) NOT NULL DEFAULT 'instant';
+ALTER TABLE rooms ADD COLUMN consensus_percent INTEGER NOT NULL DEFAULT 51;
+
+-- Each guesser's current vote in a consensus room, cleared on every reveal
+-- and turn change
+CREATE TABLE field_votes (
+    id SERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL,
+    field_id INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_game
+    FOREIGN KEY (game_id)
+    REFERENCES games (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_field
+    FOREIGN KEY (field_id)
+    REFERENCES fields (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_player
+    FOREIGN KEY (player_id)
+    REFERENCES players (id)
+    ON DELETE CASCADE,
+    CONSTRAINT uq_field_votes_game_player
+    UNIQUE (game_id, player_id)
+);
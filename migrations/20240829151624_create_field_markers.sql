@@ -0,0 +1,25 @@
+-- Cards a guesser tentatively marked for their team, cleared when the turn
+-- ends and never sent to the other teams
+CREATE TABLE field_markers (
+    id SERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL,
+    field_id INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    team VARCHAR(50) NOT NULL,
+    kind VARCHAR(10) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_game
+    FOREIGN KEY (game_id)
+    REFERENCES games (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_field
+    FOREIGN KEY (field_id)
+    REFERENCES fields (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_player
+    FOREIGN KEY (player_id)
+    REFERENCES players (id)
+    ON DELETE CASCADE,
+    CONSTRAINT uq_field_markers_field_player
+    UNIQUE (field_id, player_id)
+);
@@ -0,0 +1,24 @@
+-- Whether showers are kept out of the chat while their guessers are at it
+ALTER TABLE rooms ADD COLUMN mute_showers_while_guessing BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Room chat, either for the whole room or for the sender's team only
+CREATE TABLE chat_messages (
+    id SERIAL PRIMARY KEY,
+    room_id INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    username VARCHAR(255) NOT NULL,
+    channel VARCHAR(10) NOT NULL,
+    team VARCHAR(50) NOT NULL,
+    text VARCHAR(300) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_room
+    FOREIGN KEY (room_id)
+    REFERENCES rooms (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_player
+    FOREIGN KEY (player_id)
+    REFERENCES players (id)
+    ON DELETE CASCADE
+);
+
+CREATE INDEX idx_chat_messages_room_id ON chat_messages (room_id);
@@ -0,0 +1,33 @@
+-- Quick reactions to a card or a player, counted in the game's summary
+CREATE TABLE reactions (
+    id SERIAL PRIMARY KEY,
+    room_id INTEGER NOT NULL,
+    game_id INTEGER,
+    player_id INTEGER NOT NULL,
+    reaction VARCHAR(20) NOT NULL,
+    field_id INTEGER,
+    target_player_id INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_room
+    FOREIGN KEY (room_id)
+    REFERENCES rooms (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_game
+    FOREIGN KEY (game_id)
+    REFERENCES games (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_player
+    FOREIGN KEY (player_id)
+    REFERENCES players (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_field
+    FOREIGN KEY (field_id)
+    REFERENCES fields (id)
+    ON DELETE CASCADE,
+    CONSTRAINT fk_target_player
+    FOREIGN KEY (target_player_id)
+    REFERENCES players (id)
+    ON DELETE CASCADE
+);
+
+CREATE INDEX idx_reactions_game_id ON reactions (game_id);
@@ -0,0 +1,2 @@
+-- Recent games of a room are looked up on every board draw and room load
+CREATE INDEX idx_games_room_id_created_at ON games (room_id, created_at);
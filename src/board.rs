@@ -0,0 +1,691 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    models::Team,
+    types::{Difficulty, WordDifficulty},
+};
+
+pub const MIN_BOARD_SIDE: i32 = 4;
+pub const MAX_BOARD_SIDE: i32 = 7;
+pub const DEFAULT_BOARD_SIDE: i32 = 5;
+pub const MAX_AVOID_RECENT_GAMES: i32 = 10;
+pub const MIN_TEAMS: i32 = 2;
+pub const MAX_TEAMS: i32 = 3;
+/// Turns a Duet game has to find every green card in.
+pub const DUET_TURNS: i32 = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardSize {
+    pub rows: i32,
+    pub cols: i32,
+}
+
+impl BoardSize {
+    pub fn new(rows: i32, cols: i32) -> Result<Self, String> {
+        let valid = MIN_BOARD_SIDE..=MAX_BOARD_SIDE;
+        if !valid.contains(&rows) || !valid.contains(&cols) {
+            return Err(format!(
+                "Board dimensions must be between {MIN_BOARD_SIDE} and {MAX_BOARD_SIDE}, got {rows}x{cols}"
+            ));
+        }
+        Ok(BoardSize { rows, cols })
+    }
+
+    pub fn cells(&self) -> usize {
+        (self.rows * self.cols) as usize
+    }
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        BoardSize {
+            rows: DEFAULT_BOARD_SIDE,
+            cols: DEFAULT_BOARD_SIDE,
+        }
+    }
+}
+
+/// How many cards of each kind a board gets. The ratios follow the classic
+/// 5x5 board (7 red, 6 blue, 1 black, 11 neutral), or 6, 5 and 5 team cards
+/// with 8 neutral ones for three teams, and are scaled to the cell count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardCounts {
+    pub starting_team: usize,
+    /// Cards of each team after the starting one.
+    pub other_team: usize,
+    pub assassins: usize,
+    pub neutral: usize,
+}
+
+impl CardCounts {
+    pub fn for_size(size: &BoardSize, teams: usize) -> Self {
+        let cells = size.cells();
+        let per_25 = if teams > 2 { 6 } else { 7 };
+        let starting_team = (cells * per_25 + 12) / 25;
+        let other_team = starting_team - 1;
+        let assassins = cells.div_ceil(25);
+        CardCounts {
+            starting_team,
+            other_team,
+            assassins,
+            neutral: cells - starting_team - other_team * (teams - 1) - assassins,
+        }
+    }
+}
+
+/// The teams of a game with `count` teams.
+pub fn teams_for_count(count: i32) -> Vec<Team> {
+    [Team::Red, Team::Blue, Team::Yellow]
+        .into_iter()
+        .take(count.clamp(MIN_TEAMS, MAX_TEAMS) as usize)
+        .collect()
+}
+
+/// `teams` in turn order, starting with `starting_team`.
+pub fn turn_order(teams: &[Team], starting_team: &Team) -> Vec<Team> {
+    let start = teams
+        .iter()
+        .position(|team| team == starting_team)
+        .unwrap_or(0);
+    teams[start..]
+        .iter()
+        .chain(&teams[..start])
+        .cloned()
+        .collect()
+}
+
+/// The RNG every board is generated with. ChaCha8 produces the same stream on
+/// every platform and rand_chacha release, so a seed always gives the same board.
+pub fn seeded_rng(seed: i64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed as u64)
+}
+
+/// A fresh seed, kept short enough to read out to other players.
+pub fn random_seed() -> i64 {
+    rand::thread_rng().gen_range(0..=u32::MAX as i64)
+}
+
+/// One of `teams` to go first. Two teams are told apart by the low bit of the
+/// draw, which keeps the boards of existing two-team seeds.
+pub fn seeded_starting_team<R: RngCore>(teams: &[Team], rng: &mut R) -> Team {
+    if teams.len() == 2 {
+        return teams[(rng.next_u32() & 1) as usize].clone();
+    }
+    teams[index_below(teams.len(), rng)].clone()
+}
+
+/// Uniform index in `0..bound`. Written out instead of using `gen_range` so the
+/// mapping from RNG output to boards cannot change with a rand upgrade.
+fn index_below<R: RngCore>(bound: usize, rng: &mut R) -> usize {
+    ((rng.next_u32() as u64 * bound as u64) >> 32) as usize
+}
+
+/// Fisher-Yates shuffle, see `index_below` for why it is not `SliceRandom`.
+fn shuffle<T, R: RngCore>(items: &mut [T], rng: &mut R) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, index_below(i + 1, rng));
+    }
+}
+
+/// `count` distinct items in random order, from a partial Fisher-Yates shuffle.
+fn sample<'a, T, R: RngCore>(items: &'a [T], count: usize, rng: &mut R) -> Vec<&'a T> {
+    let mut picked = items.iter().collect::<Vec<_>>();
+    let count = count.min(picked.len());
+    for i in 0..count {
+        let j = i + index_below(picked.len() - i, rng);
+        picked.swap(i, j);
+    }
+    picked.truncate(count);
+    picked
+}
+
+#[derive(Debug)]
+pub struct GeneratedField {
+    pub text: String,
+    pub team: Team,
+    /// The blue side's color on a Duet board.
+    pub team_b: Option<Team>,
+    pub position: i32,
+    pub row_index: i32,
+    pub col_index: i32,
+}
+
+/// Like `sample`, but skips words whose similarity group (see
+/// `WordPack::similarity_groups`) is already in `used`, and adds the groups of
+/// the words it picks. Words missing from `groups` are their own group.
+fn sample_distinct<'a, R: RngCore>(
+    words: &[&'a String],
+    count: usize,
+    groups: &HashMap<String, String>,
+    used: &mut HashSet<String>,
+    rng: &mut R,
+) -> Vec<&'a String> {
+    let mut candidates = words.to_vec();
+    let mut picked = Vec::with_capacity(count);
+    for i in 0..candidates.len() {
+        if picked.len() == count {
+            break;
+        }
+        let j = i + index_below(candidates.len() - i, rng);
+        candidates.swap(i, j);
+        let word = candidates[i].to_lowercase();
+        let group = groups.get(&word).cloned().unwrap_or(word);
+        if used.insert(group) {
+            picked.push(candidates[i]);
+        }
+    }
+    picked
+}
+
+/// Like `sample_distinct`, but only takes words in `avoid` (lowercased) once
+/// every other word has been used up.
+fn sample_avoiding<'a, R: RngCore>(
+    words: &[&'a String],
+    count: usize,
+    avoid: &HashSet<String>,
+    groups: &HashMap<String, String>,
+    used: &mut HashSet<String>,
+    rng: &mut R,
+) -> Vec<&'a String> {
+    let (fresh, stale): (Vec<_>, Vec<_>) = words
+        .iter()
+        .copied()
+        .partition(|word| !avoid.contains(&word.to_lowercase()));
+    let mut picked = sample_distinct(&fresh, count, groups, used, rng);
+    let missing = count - picked.len();
+    picked.extend(sample_distinct(&stale, missing, groups, used, rng));
+    picked
+}
+
+/// What the words of a board are drawn from, see `choose_board_words`.
+pub struct WordPool<'a> {
+    pub pack_words: &'a [String],
+    pub custom_words: &'a [String],
+    /// Percentage of the board taken from `custom_words`.
+    pub custom_ratio: i32,
+    /// Lowercased word to similarity group, from `WordPack::similarity_groups`.
+    pub groups: &'a HashMap<String, String>,
+    /// Lowercased word to difficulty; missing words are normal.
+    pub difficulties: &'a HashMap<String, WordDifficulty>,
+    pub difficulty: &'a Difficulty,
+    /// Lowercased words of the room's recent boards.
+    pub recent_words: &'a HashSet<String>,
+}
+
+/// Percentage of pack words of each difficulty a board aims for.
+fn difficulty_shares(difficulty: &Difficulty) -> [(WordDifficulty, usize); 3] {
+    let (easy, normal, hard) = match difficulty {
+        Difficulty::Easy => (60, 40, 0),
+        Difficulty::Normal => (20, 60, 20),
+        Difficulty::Hard => (0, 40, 60),
+        Difficulty::Mixed => (34, 33, 33),
+    };
+    [
+        (WordDifficulty::Easy, easy),
+        (WordDifficulty::Normal, normal),
+        (WordDifficulty::Hard, hard),
+    ]
+}
+
+/// Picks `count` words with no two from the same similarity group, taking
+/// about `custom_ratio` percent of them from the room's custom list and
+/// filling the rest from the word pack in the room's difficulty mix. When a
+/// difficulty runs short the rest of the pack makes up for it, and recent
+/// words are only used when the lists run out of others.
+pub fn choose_board_words<R: RngCore>(pool: &WordPool, count: usize, rng: &mut R) -> Vec<String> {
+    let custom_target = (count * pool.custom_ratio.clamp(0, 100) as usize + 50) / 100;
+    let mut used = HashSet::new();
+    let custom_words = pool.custom_words.iter().collect::<Vec<_>>();
+    let mut chosen = sample_avoiding(
+        &custom_words,
+        custom_target,
+        pool.recent_words,
+        pool.groups,
+        &mut used,
+        rng,
+    );
+
+    let pack_count = count - chosen.len();
+    for (difficulty, share) in difficulty_shares(pool.difficulty) {
+        let tier = pool
+            .pack_words
+            .iter()
+            .filter(|word| {
+                let rated = pool.difficulties.get(&word.to_lowercase());
+                *rated.unwrap_or(&WordDifficulty::Normal) == difficulty
+            })
+            .collect::<Vec<_>>();
+        let target = ((pack_count * share + 50) / 100).min(count - chosen.len());
+        chosen.extend(sample_avoiding(
+            &tier,
+            target,
+            pool.recent_words,
+            pool.groups,
+            &mut used,
+            rng,
+        ));
+    }
+
+    let pack_words = pool.pack_words.iter().collect::<Vec<_>>();
+    let missing = count - chosen.len();
+    chosen.extend(sample_avoiding(
+        &pack_words,
+        missing,
+        pool.recent_words,
+        pool.groups,
+        &mut used,
+        rng,
+    ));
+    chosen.into_iter().cloned().collect()
+}
+
+/// Lays out a board for `teams`, given in turn order.
+pub fn generate_board<R: RngCore>(
+    words: &[String],
+    size: &BoardSize,
+    teams: &[Team],
+    rng: &mut R,
+) -> Result<Vec<GeneratedField>, String> {
+    let cells = size.cells();
+    if words.len() < cells {
+        return Err(format!(
+            "Not enough words for a {}x{} board: need {}, have {}",
+            size.rows,
+            size.cols,
+            cells,
+            words.len()
+        ));
+    }
+
+    let words = sample(words, cells, rng);
+    let counts = CardCounts::for_size(size, teams.len());
+
+    let mut colors = Vec::with_capacity(cells);
+    colors.extend((0..counts.starting_team).map(|_| teams[0].clone()));
+    for team in &teams[1..] {
+        colors.extend((0..counts.other_team).map(|_| team.clone()));
+    }
+    colors.extend((0..counts.assassins).map(|_| Team::Black));
+    colors.extend((0..counts.neutral).map(|_| Team::Neutral));
+    shuffle(&mut colors, rng);
+
+    let fields = words
+        .into_iter()
+        .zip(colors)
+        .enumerate()
+        .map(|(i, (word, team))| GeneratedField {
+            text: word.clone(),
+            team,
+            team_b: None,
+            position: i as i32,
+            row_index: i as i32 / size.cols,
+            col_index: i as i32 % size.cols,
+        })
+        .collect();
+    Ok(fields)
+}
+
+/// How the two keys of a Duet board overlap, as (red side, blue side, cards).
+/// Each side sees 9 green cards and 3 assassins; 15 cards are green for
+/// someone, and one assassin is an assassin for both.
+const DUET_KEY: [(Team, Team, usize); 9] = [
+    (Team::Green, Team::Green, 3),
+    (Team::Green, Team::Neutral, 5),
+    (Team::Green, Team::Black, 1),
+    (Team::Neutral, Team::Green, 5),
+    (Team::Black, Team::Green, 1),
+    (Team::Black, Team::Black, 1),
+    (Team::Black, Team::Neutral, 1),
+    (Team::Neutral, Team::Black, 1),
+    (Team::Neutral, Team::Neutral, 7),
+];
+
+/// A Duet board is always the classic 5x5 one.
+pub fn duet_board_size() -> BoardSize {
+    BoardSize {
+        rows: DEFAULT_BOARD_SIDE,
+        cols: DEFAULT_BOARD_SIDE,
+    }
+}
+
+/// Lays out a Duet board with a key for each side, see `DUET_KEY`.
+pub fn generate_duet_board<R: RngCore>(
+    words: &[String],
+    rng: &mut R,
+) -> Result<Vec<GeneratedField>, String> {
+    let size = duet_board_size();
+    let cells = size.cells();
+    if words.len() < cells {
+        return Err(format!(
+            "Not enough words for a Duet board: need {}, have {}",
+            cells,
+            words.len()
+        ));
+    }
+
+    let words = sample(words, cells, rng);
+    let mut keys = DUET_KEY
+        .iter()
+        .flat_map(|(team, team_b, count)| (0..*count).map(move |_| (team.clone(), team_b.clone())))
+        .collect::<Vec<_>>();
+    shuffle(&mut keys, rng);
+
+    let fields = words
+        .into_iter()
+        .zip(keys)
+        .enumerate()
+        .map(|(i, (word, (team, team_b)))| GeneratedField {
+            text: word.clone(),
+            team,
+            team_b: Some(team_b),
+            position: i as i32,
+            row_index: i as i32 / size.cols,
+            col_index: i as i32 % size.cols,
+        })
+        .collect();
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("w{i:02}")).collect()
+    }
+
+    /// Draws a board the way a game does: starting team first, then the layout.
+    fn seeded_board(seed: i64, size: &BoardSize, team_count: i32) -> (Team, Vec<GeneratedField>) {
+        let mut rng = seeded_rng(seed);
+        let teams = teams_for_count(team_count);
+        let starting_team = seeded_starting_team(&teams, &mut rng);
+        let teams = turn_order(&teams, &starting_team);
+        let fields = generate_board(&words(60), size, &teams, &mut rng).unwrap();
+        (starting_team, fields)
+    }
+
+    fn team_letter(team: &Team) -> char {
+        match team {
+            Team::Red => 'R',
+            Team::Blue => 'B',
+            Team::Yellow => 'Y',
+            Team::Neutral => '.',
+            Team::Black => 'X',
+            Team::Green => 'G',
+        }
+    }
+
+    fn layout(fields: &[GeneratedField]) -> String {
+        fields
+            .iter()
+            .map(|field| team_letter(&field.team))
+            .collect()
+    }
+
+    fn board_words(fields: &[GeneratedField]) -> Vec<&str> {
+        fields.iter().map(|field| field.text.as_str()).collect()
+    }
+
+    #[test]
+    fn seed_gives_an_exact_two_team_board() {
+        let (starting_team, fields) = seeded_board(42, &BoardSize::default(), 2);
+        assert_eq!(starting_team, Team::Blue);
+        assert_eq!(layout(&fields), "RRXRRBR.B..B...BBR.BB....");
+        assert_eq!(
+            board_words(&fields),
+            [
+                "w40", "w09", "w57", "w47", "w27", "w23", "w39", "w46", "w05", "w41", "w17", "w26",
+                "w11", "w20", "w50", "w43", "w49", "w52", "w28", "w00", "w19", "w35", "w56", "w44",
+                "w58",
+            ]
+        );
+    }
+
+    #[test]
+    fn seed_gives_an_exact_three_team_board() {
+        let (starting_team, fields) = seeded_board(7, &BoardSize::new(6, 6).unwrap(), 3);
+        assert_eq!(starting_team, Team::Red);
+        assert_eq!(layout(&fields), "RR.Y.YB..YYYYBRBRX.YRBBR.XR..Y.BBRRB");
+    }
+
+    #[test]
+    fn seed_gives_an_exact_duet_board() {
+        let fields = generate_duet_board(&words(40), &mut seeded_rng(42)).unwrap();
+        let key_b = fields
+            .iter()
+            .map(|field| team_letter(field.team_b.as_ref().unwrap()))
+            .collect::<String>();
+        assert_eq!(layout(&fields), ".G...G.G.XG.GXGG..GGX....");
+        assert_eq!(key_b, "G.G.GX....G..GGGGX..X.G..");
+    }
+
+    #[test]
+    fn same_seed_always_gives_the_same_board() {
+        for seed in [0, 1, 42, 4_294_967_295, -1] {
+            for (size, team_count) in [
+                (BoardSize::default(), 2),
+                (BoardSize::new(7, 7).unwrap(), 3),
+            ] {
+                let (team, fields) = seeded_board(seed, &size, team_count);
+                let (again_team, again) = seeded_board(seed, &size, team_count);
+                assert_eq!(team, again_team);
+                assert_eq!(layout(&fields), layout(&again));
+                assert_eq!(board_words(&fields), board_words(&again));
+            }
+        }
+    }
+
+    #[test]
+    fn a_forced_starting_team_swaps_the_seeded_colors() {
+        let (seeded_team, fields) = seeded_board(42, &BoardSize::default(), 2);
+        let mut rng = seeded_rng(42);
+        let teams = teams_for_count(2);
+        seeded_starting_team(&teams, &mut rng);
+        let forced = turn_order(&teams, &Team::Red);
+        let swapped = generate_board(&words(60), &BoardSize::default(), &forced, &mut rng).unwrap();
+        assert_eq!(seeded_team, Team::Blue);
+        assert_eq!(board_words(&swapped), board_words(&fields));
+        assert_eq!(
+            layout(&swapped),
+            layout(&fields)
+                .chars()
+                .map(|c| match c {
+                    'R' => 'B',
+                    'B' => 'R',
+                    c => c,
+                })
+                .collect::<String>()
+        );
+    }
+
+    #[test]
+    fn different_seeds_give_different_boards() {
+        let (_, first) = seeded_board(1, &BoardSize::default(), 2);
+        let (_, second) = seeded_board(2, &BoardSize::default(), 2);
+        assert_ne!(
+            (layout(&first), board_words(&first)),
+            (layout(&second), board_words(&second))
+        );
+    }
+
+    #[test]
+    fn boards_have_the_card_counts_for_their_size() {
+        for (rows, cols, team_count) in [(5, 5, 2), (4, 7, 2), (6, 6, 3), (7, 7, 3)] {
+            let size = BoardSize::new(rows, cols).unwrap();
+            let (starting_team, fields) = seeded_board(3, &size, team_count);
+            let counts = CardCounts::for_size(&size, team_count as usize);
+            let count = |team: &Team| fields.iter().filter(|field| field.team == *team).count();
+            assert_eq!(fields.len(), size.cells());
+            assert_eq!(count(&starting_team), counts.starting_team);
+            for team in teams_for_count(team_count)
+                .iter()
+                .filter(|team| **team != starting_team)
+            {
+                assert_eq!(count(team), counts.other_team);
+            }
+            assert_eq!(count(&Team::Black), counts.assassins);
+            assert_eq!(count(&Team::Neutral), counts.neutral);
+        }
+    }
+
+    #[test]
+    fn shuffle_and_sample_keep_the_items() {
+        let mut rng = seeded_rng(5);
+        for bound in [1, 2, 7, 100] {
+            assert!((0..50).all(|_| index_below(bound, &mut rng) < bound));
+        }
+        let mut items = (0..30).collect::<Vec<_>>();
+        shuffle(&mut items, &mut rng);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..30).collect::<Vec<_>>());
+
+        let picked = sample(&items, 10, &mut rng);
+        assert_eq!(picked.len(), 10);
+        assert_eq!(picked.iter().collect::<HashSet<_>>().len(), 10);
+        assert_eq!(sample(&items, 50, &mut rng).len(), 30);
+    }
+
+    fn draw(pack: &[String], recent: &HashSet<String>, count: usize, seed: i64) -> Vec<String> {
+        let pool = WordPool {
+            pack_words: pack,
+            custom_words: &[],
+            custom_ratio: 0,
+            groups: &HashMap::new(),
+            difficulties: &HashMap::new(),
+            difficulty: &Difficulty::Normal,
+            recent_words: recent,
+        };
+        choose_board_words(&pool, count, &mut seeded_rng(seed))
+    }
+
+    #[test]
+    fn recent_words_are_left_out_while_others_remain() {
+        let pack = words(40);
+        let recent = pack[..15].iter().cloned().collect::<HashSet<_>>();
+        for seed in 0..20 {
+            let chosen = draw(&pack, &recent, 25, seed);
+            assert_eq!(chosen.len(), 25);
+            assert!(chosen.iter().all(|word| !recent.contains(word)));
+        }
+    }
+
+    #[test]
+    fn recent_words_fill_up_a_short_pack() {
+        let pack = words(40);
+        let recent = pack[..30].iter().cloned().collect::<HashSet<_>>();
+        for seed in 0..20 {
+            let chosen = draw(&pack, &recent, 25, seed);
+            assert_eq!(chosen.iter().collect::<HashSet<_>>().len(), 25);
+            // Every fresh word is used before the recent ones make up the rest
+            assert!(pack[30..].iter().all(|word| chosen.contains(word)));
+            assert_eq!(
+                chosen.iter().filter(|word| recent.contains(*word)).count(),
+                15
+            );
+        }
+    }
+
+    #[test]
+    fn no_recent_games_leaves_the_draw_alone() {
+        let pack = words(40);
+        let unrelated = words(80)[40..].iter().cloned().collect::<HashSet<_>>();
+        let mut drawn = HashSet::new();
+        for seed in 0..20 {
+            let chosen = draw(&pack, &HashSet::new(), 25, seed);
+            assert_eq!(chosen, draw(&pack, &unrelated, 25, seed));
+            drawn.extend(chosen);
+        }
+        assert_eq!(drawn.len(), pack.len());
+    }
+
+    /// `easy` easy words, 30 normal and `hard` hard ones, with their ratings.
+    fn rated_pack(easy: usize, hard: usize) -> (Vec<String>, HashMap<String, WordDifficulty>) {
+        let rated =
+            |prefix: &'static str, count: usize| (0..count).map(move |i| format!("{prefix}{i:02}"));
+        let mut difficulties = HashMap::new();
+        difficulties.extend(rated("e", easy).map(|word| (word, WordDifficulty::Easy)));
+        difficulties.extend(rated("h", hard).map(|word| (word, WordDifficulty::Hard)));
+        let words = rated("e", easy)
+            .chain(rated("n", 30))
+            .chain(rated("h", hard))
+            .collect();
+        (words, difficulties)
+    }
+
+    /// How many easy, normal and hard words a board of `count` words gets.
+    fn difficulty_mix(
+        difficulty: Difficulty,
+        (easy, hard): (usize, usize),
+        count: usize,
+    ) -> (usize, usize, usize) {
+        let (words, difficulties) = rated_pack(easy, hard);
+        let pool = WordPool {
+            pack_words: &words,
+            custom_words: &[],
+            custom_ratio: 0,
+            groups: &HashMap::new(),
+            difficulties: &difficulties,
+            difficulty: &difficulty,
+            recent_words: &HashSet::new(),
+        };
+        let chosen = choose_board_words(&pool, count, &mut seeded_rng(9));
+        assert_eq!(chosen.iter().collect::<HashSet<_>>().len(), count);
+        let starting = |prefix| {
+            chosen
+                .iter()
+                .filter(|word| word.starts_with(prefix))
+                .count()
+        };
+        (starting("e"), starting("n"), starting("h"))
+    }
+
+    #[test]
+    fn difficulty_shares_add_up() {
+        for difficulty in [
+            Difficulty::Easy,
+            Difficulty::Normal,
+            Difficulty::Hard,
+            Difficulty::Mixed,
+        ] {
+            let shares = difficulty_shares(&difficulty);
+            assert_eq!(shares.iter().map(|(_, share)| share).sum::<usize>(), 100);
+        }
+    }
+
+    #[test]
+    fn boards_follow_the_difficulty_mix() {
+        let plenty = (30, 30);
+        assert_eq!(difficulty_mix(Difficulty::Easy, plenty, 25), (15, 10, 0));
+        assert_eq!(difficulty_mix(Difficulty::Normal, plenty, 25), (5, 15, 5));
+        assert_eq!(difficulty_mix(Difficulty::Hard, plenty, 25), (0, 10, 15));
+        // 8.5 rounds up to 9 and 8.25 down to 8
+        assert_eq!(difficulty_mix(Difficulty::Mixed, plenty, 25), (9, 8, 8));
+        assert_eq!(difficulty_mix(Difficulty::Normal, plenty, 49), (10, 29, 10));
+        assert_eq!(difficulty_mix(Difficulty::Mixed, plenty, 49), (17, 16, 16));
+    }
+
+    #[test]
+    fn rounding_never_overfills_or_underfills_the_board() {
+        // Each share of 10 rounds down to 3, the last word comes from anywhere
+        let (easy, normal, hard) = difficulty_mix(Difficulty::Mixed, (30, 30), 10);
+        assert_eq!(easy + normal + hard, 10);
+        assert!(easy >= 3 && normal >= 3 && hard >= 3);
+        for count in [16, 20, 28, 36, 42, 49] {
+            let (easy, normal, hard) = difficulty_mix(Difficulty::Easy, (30, 30), count);
+            assert_eq!(easy + normal + hard, count);
+        }
+    }
+
+    #[test]
+    fn short_tiers_are_made_up_from_the_rest_of_the_pack() {
+        let (easy, normal, hard) = difficulty_mix(Difficulty::Hard, (30, 2), 25);
+        assert_eq!(hard, 2);
+        assert_eq!(easy + normal, 23);
+        // A pack without ratings draws all its words as normal ones
+        assert_eq!(difficulty_mix(Difficulty::Easy, (0, 0), 25), (0, 25, 0));
+    }
+}
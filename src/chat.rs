@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDateTime};
+use tokio::sync::RwLock;
+
+use crate::{
+    game_flow::player_room,
+    models::{ChatMessage, Player, Reaction},
+    my_state::MyState,
+    repositories::{
+        chat_repository::{create_chat_message, get_chat_history},
+        field_repository::get_field_by_id,
+        game_repository::{get_current_game_for_room, get_game_by_id},
+        player_repository::{get_player_by_id, get_players_by_room_id},
+        reaction_repository::create_reaction,
+        room_repository::get_room_by_id,
+    },
+    types::{ChatChannel, ChatMessageRequest, GameStage, ReactionRequest, Role, TurnPhase},
+};
+
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 300;
+/// Messages a player gets when joining a room.
+pub const CHAT_HISTORY_LENGTH: i64 = 50;
+/// A player can send at most this many messages per rate limit window.
+pub const CHAT_RATE_LIMIT_MESSAGES: usize = 5;
+pub const CHAT_RATE_LIMIT_SECONDS: i64 = 10;
+/// Reactions are throttled the same way, with a shorter window.
+pub const REACTION_RATE_LIMIT: usize = 3;
+pub const REACTION_RATE_LIMIT_SECONDS: i64 = 5;
+
+/// The message with surrounding whitespace trimmed, if its length is allowed.
+pub fn chat_message_text(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Message is empty".to_string());
+    }
+    if text.chars().count() > MAX_CHAT_MESSAGE_LENGTH {
+        return Err(format!(
+            "Message can be at most {MAX_CHAT_MESSAGE_LENGTH} characters long"
+        ));
+    }
+    Ok(text.to_string())
+}
+
+/// Records an event sent at `now` unless the times in `sent` already reach
+/// `limit` within the last `window_seconds`. Times that fell out of the window
+/// are dropped.
+pub fn take_rate_limit_slot(
+    sent: &mut Vec<NaiveDateTime>,
+    now: NaiveDateTime,
+    limit: usize,
+    window_seconds: i64,
+) -> bool {
+    let window_start = now - Duration::seconds(window_seconds);
+    sent.retain(|time| *time > window_start);
+    if sent.len() >= limit {
+        return false;
+    }
+    sent.push(now);
+    true
+}
+
+/// Checks that the player may send the message, stores it and delivers it to
+/// the room or to the player's team. `player_id` is whoever the socket joined
+/// the room as, never taken from the request.
+pub async fn send_chat_message(
+    state: Arc<RwLock<MyState>>,
+    player_id: i32,
+    request: &ChatMessageRequest,
+) -> Result<ChatMessage, String> {
+    let text = chat_message_text(&request.text)?;
+    let player = get_player_by_id(state.clone(), player_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Player not found".to_string())?;
+    if request.channel == ChatChannel::Team && player.role == Role::Spectator {
+        return Err("Spectators have no team channel".to_string());
+    }
+    if player.role == Role::Shower {
+        let room = get_room_by_id(state.clone(), player.room_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let game = get_current_game_for_room(state.clone(), player.room_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let guessing = game.is_some_and(|game| {
+            !matches!(game.game_stage, GameStage::Finished) && game.turn_phase == TurnPhase::Guess
+        });
+        if room.mute_showers_while_guessing && guessing {
+            return Err("Showers cannot chat while guessers are guessing".to_string());
+        }
+    }
+    {
+        let mut state = state.write().await;
+        let now = state.clock.now();
+        let sent = state.chat_rate_limits.entry(player.id).or_default();
+        if !take_rate_limit_slot(sent, now, CHAT_RATE_LIMIT_MESSAGES, CHAT_RATE_LIMIT_SECONDS) {
+            return Err("You are sending messages too fast".to_string());
+        }
+    }
+
+    let message = create_chat_message(state.clone(), &player, &request.channel, &text)
+        .await
+        .map_err(|e| e.to_string())?;
+    match request.channel {
+        ChatChannel::All => {
+            state
+                .read()
+                .await
+                .io
+                .to(player.room_id.to_string())
+                .emit("chat-message", &message)
+                .ok();
+        }
+        ChatChannel::Team => {
+            let teammates = get_players_by_room_id(state.clone(), player.room_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|teammate| teammate.team == player.team && teammate.role != Role::Spectator)
+                .map(|teammate| player_room(teammate.id))
+                .collect::<Vec<_>>();
+            state
+                .read()
+                .await
+                .io
+                .to(teammates)
+                .emit("chat-message", &message)
+                .ok();
+        }
+    }
+    Ok(message)
+}
+
+/// The recent messages `player` may read: the room channel and their team's.
+/// Spectators have no team, so they only read the room channel.
+pub async fn chat_history_for_player(
+    state: Arc<RwLock<MyState>>,
+    player: &Player,
+) -> Result<Vec<ChatMessage>, String> {
+    let team = (player.role != Role::Spectator).then_some(&player.team);
+    get_chat_history(state, player.room_id, team, CHAT_HISTORY_LENGTH)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Stores a player's reaction to a card or another player and shows it to the
+/// room. Reactions count towards the summary of the game they were sent in.
+/// Like chat, `player_id` comes from the socket's binding.
+pub async fn send_reaction(
+    state: Arc<RwLock<MyState>>,
+    player_id: i32,
+    request: &ReactionRequest,
+) -> Result<Reaction, String> {
+    let player = get_player_by_id(state.clone(), player_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Player not found".to_string())?;
+    let game_id = match (request.field_id, request.target_player_id) {
+        (Some(field_id), None) => {
+            let field = get_field_by_id(state.clone(), field_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("Field not found".to_string())?;
+            let game = get_game_by_id(state.clone(), field.game_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("Game not found".to_string())?;
+            if game.room_id != player.room_id {
+                return Err("Field is not in your room".to_string());
+            }
+            Some(game.id)
+        }
+        (None, Some(target_player_id)) => {
+            let target = get_player_by_id(state.clone(), target_player_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("Player not found".to_string())?;
+            if target.room_id != player.room_id {
+                return Err("Player is not in your room".to_string());
+            }
+            get_current_game_for_room(state.clone(), player.room_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|game| game.id)
+        }
+        _ => return Err("React to either a card or a player".to_string()),
+    };
+    {
+        let mut state = state.write().await;
+        let now = state.clock.now();
+        let sent = state.reaction_rate_limits.entry(player.id).or_default();
+        if !take_rate_limit_slot(sent, now, REACTION_RATE_LIMIT, REACTION_RATE_LIMIT_SECONDS) {
+            return Err("You are reacting too fast".to_string());
+        }
+    }
+
+    let reaction = create_reaction(
+        state.clone(),
+        player.room_id,
+        game_id,
+        player.id,
+        &request.reaction,
+        request.field_id,
+        request.target_player_id,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    state
+        .read()
+        .await
+        .io
+        .to(player.room_id.to_string())
+        .emit("reaction", &reaction)
+        .ok();
+    Ok(reaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> NaiveDateTime {
+        NaiveDateTime::default() + Duration::seconds(seconds)
+    }
+
+    #[test]
+    fn messages_are_trimmed_and_limited_in_length() {
+        assert_eq!(chat_message_text("  hej  ").unwrap(), "hej");
+        assert!(chat_message_text(" \n ").is_err());
+        let longest = "ż".repeat(MAX_CHAT_MESSAGE_LENGTH);
+        assert_eq!(chat_message_text(&longest).unwrap(), longest);
+        assert!(chat_message_text(&format!("{longest}a")).is_err());
+    }
+
+    #[test]
+    fn rate_limit_allows_up_to_the_limit_per_window() {
+        let mut sent = Vec::new();
+        for second in 0..3 {
+            assert!(take_rate_limit_slot(&mut sent, at(second), 3, 5));
+        }
+        assert!(!take_rate_limit_slot(&mut sent, at(4), 3, 5));
+        // Rejected attempts do not take a slot
+        assert_eq!(sent.len(), 3);
+        // The first message leaves the window five seconds after it was sent
+        assert!(take_rate_limit_slot(&mut sent, at(5), 3, 5));
+        assert!(!take_rate_limit_slot(&mut sent, at(5), 3, 5));
+    }
+
+    #[test]
+    fn rate_limit_drops_times_outside_the_window() {
+        let mut sent = vec![at(0), at(1), at(2)];
+        assert!(take_rate_limit_slot(&mut sent, at(60), 3, 10));
+        assert_eq!(sent, [at(60)]);
+    }
+}
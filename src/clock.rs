@@ -0,0 +1,58 @@
+use std::{future::Future, pin::Pin};
+
+use chrono::{NaiveDateTime, Utc};
+
+/// Source of the current time for timers, so they can run on a fake clock.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> NaiveDateTime;
+
+    /// Waits until the clock reaches `deadline`. Timers sleep through this
+    /// rather than on their own, so a fake clock can make them fire.
+    fn sleep_until(&self, deadline: NaiveDateTime) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Utc::now().naive_utc()
+    }
+
+    fn sleep_until(&self, deadline: NaiveDateTime) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let wait = (deadline - self.now()).to_std().unwrap_or_default();
+        Box::pin(tokio::time::sleep(wait))
+    }
+}
+
+/// A clock that only moves when told to, waking the timers it passes.
+#[cfg(test)]
+pub struct FakeClock {
+    now: tokio::sync::watch::Sender<NaiveDateTime>,
+}
+
+#[cfg(test)]
+impl FakeClock {
+    pub fn new(now: NaiveDateTime) -> Self {
+        FakeClock {
+            now: tokio::sync::watch::Sender::new(now),
+        }
+    }
+
+    pub fn advance(&self, by: chrono::Duration) {
+        self.now.send_modify(|now| *now += by);
+    }
+}
+
+#[cfg(test)]
+impl Clock for FakeClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.now.borrow()
+    }
+
+    fn sleep_until(&self, deadline: NaiveDateTime) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let mut now = self.now.subscribe();
+        Box::pin(async move {
+            now.wait_for(|now| *now >= deadline).await.ok();
+        })
+    }
+}
@@ -0,0 +1,499 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    sync::Arc,
+};
+
+use sqlx::PgConnection;
+use tokio::sync::RwLock;
+
+use crate::{
+    board::{
+        choose_board_words, duet_board_size, generate_board, generate_duet_board, random_seed,
+        seeded_rng, seeded_starting_team, teams_for_count, turn_order, BoardSize, GeneratedField,
+        WordPool, DUET_TURNS,
+    },
+    models::{Field, FieldVote, Game, Room, Team},
+    my_state::MyState,
+    repositories::{
+        custom_word_repository::get_custom_words_for_room,
+        field_repository::{
+            create_fields_for_game, get_fields_for_game_id, get_recent_words_for_room,
+            mark_field_as_neutral_for, mark_field_as_used,
+        },
+        game_repository::{
+            add_eliminated_team, change_game_current_team, create_game, finish_game,
+            get_game_by_id, use_guess,
+        },
+        marker_repository::{delete_field_markers_for_game, get_field_markers_for_team},
+        player_repository::get_players_by_room_id,
+        reaction_repository::get_reaction_counts_for_game,
+        room_repository::get_room_by_id,
+        score_repository::{
+            get_player_scores_for_room, get_team_scores_for_room, record_player_results,
+        },
+        time_bank_repository::create_time_banks,
+        vote_repository::delete_field_votes_for_game,
+        word_pack_repository::{get_community_word_pack_by_id, get_community_word_pack_words},
+    },
+    rules::{
+        duet_board_cleared, duet_reveal, next_duet_clue_giver, points_for_duet_win, points_for_win,
+        winner_after_reveal, DuetReveal,
+    },
+    turn_timer::{charge_time_bank, current_time_banks, start_turn_timer},
+    types::{GameMode, GameSummary, RoomScore, RoomSettings, WordPackStatus},
+    word_packs::{parse_community_word_pack_key, WordPack},
+};
+
+/// Looks up a built-in pack by name or a community pack by its key. Community
+/// packs are only found while approved, so words that went back to moderation
+/// after an edit never reach a board.
+pub async fn find_word_pack(
+    state: Arc<RwLock<MyState>>,
+    key: &str,
+) -> Result<Option<WordPack>, Box<dyn Error>> {
+    if parse_community_word_pack_key(key).is_some() {
+        let pack = find_community_word_pack(state, key).await?;
+        return Ok(
+            pack.and_then(|(pack, status)| (status == WordPackStatus::Approved).then_some(pack))
+        );
+    }
+    let pack = state
+        .read()
+        .await
+        .word_packs
+        .iter()
+        .find(|pack| pack.name == key)
+        .cloned();
+    Ok(pack)
+}
+
+async fn find_community_word_pack(
+    state: Arc<RwLock<MyState>>,
+    key: &str,
+) -> Result<Option<(WordPack, WordPackStatus)>, Box<dyn Error>> {
+    let Some(id) = parse_community_word_pack_key(key) else {
+        return Ok(None);
+    };
+    let Some(community_pack) = get_community_word_pack_by_id(state.clone(), id).await? else {
+        return Ok(None);
+    };
+    let words = get_community_word_pack_words(state, id).await?;
+    let pack = WordPack {
+        name: key.to_string(),
+        language: community_pack.language,
+        tags: community_pack.tags,
+        words,
+        groups: Vec::new(),
+        stems: HashMap::new(),
+        difficulties: HashMap::new(),
+    };
+    Ok(Some((pack, community_pack.status)))
+}
+
+/// The packs room settings may pick from: every built-in pack plus the
+/// community pack the settings ask for, if it has been approved.
+pub async fn word_packs_for_settings(
+    state: Arc<RwLock<MyState>>,
+    settings: &RoomSettings,
+) -> Result<Vec<WordPack>, Box<dyn Error>> {
+    let mut packs = state.read().await.word_packs.clone();
+    if let Some(key) = &settings.word_pack {
+        if let Some((pack, WordPackStatus::Approved)) = find_community_word_pack(state, key).await?
+        {
+            packs.push(pack);
+        }
+    }
+    Ok(packs)
+}
+
+/// A board drawn from a room's settings, not stored yet.
+pub struct NewGame {
+    /// The playing teams in turn order.
+    pub teams: Vec<Team>,
+    pub size: BoardSize,
+    pub seed: i64,
+    pub turns: Option<i32>,
+    pub fields: Vec<GeneratedField>,
+}
+
+/// Draws a fresh board from the room's settings and `custom_words`.
+///
+/// Given a seed, the words, layout and starting team all come from it, so the
+/// same seed with the same settings and word lists gives the same board.
+/// Without one a fresh seed is drawn. `starting_team`, if it plays, replaces
+/// the seeded team either way; the board is then the seeded one with the colors
+/// swapped, so a game is replayed from its stored seed and starting team.
+/// Words from the room's recent boards are avoided unless a seed is given, so
+/// a shared seed reproduces its board in any room; a board drawn without one
+/// also depends on the words the room saw before it. Duet games always get a
+/// 5x5 board with a key for each side and a limited number of turns.
+pub async fn draw_new_game(
+    state: Arc<RwLock<MyState>>,
+    room: &Room,
+    custom_words: &[String],
+    seed: Option<i64>,
+    starting_team: Option<&Team>,
+) -> Result<NewGame, Box<dyn Error>> {
+    let pack = find_word_pack(state.clone(), &room.word_pack).await?;
+    let pack = match pack {
+        Some(pack) => pack,
+        // A community pack that is under moderation again or was deleted is
+        // stood in for by the first built-in pack in the room's language
+        None if parse_community_word_pack_key(&room.word_pack).is_some() => state
+            .read()
+            .await
+            .word_packs
+            .iter()
+            .find(|pack| pack.language == room.language)
+            .cloned()
+            .ok_or_else(|| format!("No word pack in {}", room.language.to_string()))?,
+        None => return Err(format!("Unknown word pack {}", room.word_pack).into()),
+    };
+    let size = match room.game_mode {
+        GameMode::Classic => BoardSize::new(room.board_rows, room.board_cols)?,
+        GameMode::Duet => duet_board_size(),
+    };
+    let recent_words = match seed {
+        None => get_recent_words_for_room(state.clone(), room.id, room.avoid_recent_games as i64)
+            .await?
+            .into_iter()
+            .collect(),
+        Some(_) => HashSet::new(),
+    };
+    let seed = seed.unwrap_or_else(random_seed);
+    let mut rng = seeded_rng(seed);
+    let teams = match room.game_mode {
+        GameMode::Classic => teams_for_count(room.team_count),
+        GameMode::Duet => teams_for_count(2),
+    };
+    let seeded_team = seeded_starting_team(&teams, &mut rng);
+    let starting_team = starting_team
+        .filter(|team| teams.contains(team))
+        .cloned()
+        .unwrap_or(seeded_team);
+    let teams = turn_order(&teams, &starting_team);
+    let pool = WordPool {
+        pack_words: &pack.words,
+        custom_words,
+        custom_ratio: room.custom_word_ratio,
+        groups: &pack.similarity_groups(),
+        difficulties: &pack.word_difficulties(),
+        difficulty: &room.difficulty,
+        recent_words: &recent_words,
+    };
+    let words = choose_board_words(&pool, size.cells(), &mut rng);
+    let (fields, turns) = match room.game_mode {
+        GameMode::Classic => (generate_board(&words, &size, &teams, &mut rng)?, None),
+        GameMode::Duet => (generate_duet_board(&words, &mut rng)?, Some(DUET_TURNS)),
+    };
+    Ok(NewGame {
+        teams,
+        size,
+        seed,
+        turns,
+        fields,
+    })
+}
+
+/// Stores a drawn board as the room's new game, with its time banks.
+pub async fn store_new_game(
+    conn: &mut PgConnection,
+    room: &Room,
+    new_game: NewGame,
+) -> Result<Game, Box<dyn Error>> {
+    let NewGame {
+        teams,
+        size,
+        seed,
+        turns,
+        fields,
+    } = new_game;
+    let game = create_game(&mut *conn, room.id, &teams, &size, seed, turns).await?;
+    create_fields_for_game(&mut *conn, game.id, fields).await?;
+    if room.time_bank_seconds > 0 {
+        let remaining_ms = room.time_bank_seconds as i64 * 1000;
+        create_time_banks(&mut *conn, game.id, &teams, remaining_ms).await?;
+    }
+    Ok(game)
+}
+
+/// Draws a board for the room as it is stored and saves it as a new game.
+pub async fn start_new_game(
+    state: Arc<RwLock<MyState>>,
+    room: &Room,
+    seed: Option<i64>,
+    starting_team: Option<&Team>,
+) -> Result<Game, Box<dyn Error>> {
+    let custom_words = get_custom_words_for_room(state.clone(), room.id).await?;
+    let new_game = draw_new_game(state.clone(), room, &custom_words, seed, starting_team).await?;
+    let pool = state.read().await.pool.clone();
+    let mut transaction = pool.begin().await?;
+    let game = store_new_game(&mut transaction, room, new_game).await?;
+    transaction.commit().await?;
+    Ok(game)
+}
+
+pub async fn get_room_score(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<RoomScore, Box<dyn Error>> {
+    let teams = get_team_scores_for_room(state.clone(), room_id).await?;
+    let players = get_player_scores_for_room(state, room_id).await?;
+    Ok(RoomScore { teams, players })
+}
+
+/// Passes the turn to the next team, starts its timer and tells the room. A
+/// Duet game that runs out of turns is lost instead.
+pub async fn end_turn(state: Arc<RwLock<MyState>>, game_id: i32) -> Result<Game, Box<dyn Error>> {
+    let Some(game) = get_game_by_id(state.clone(), game_id).await? else {
+        return Err("Game not found".into());
+    };
+    let room = get_room_by_id(state.clone(), game.room_id).await?;
+    charge_time_bank(state.clone(), &game).await?;
+    clear_field_votes(state.clone(), &game).await?;
+    clear_field_markers(state.clone(), &game).await?;
+    let next_team = match room.game_mode {
+        GameMode::Classic => game.next_team(),
+        GameMode::Duet => {
+            let fields = get_fields_for_game_id(state.clone(), game_id).await?;
+            next_duet_clue_giver(&game.current_team, &fields)
+        }
+    };
+    let game = change_game_current_team(state.clone(), game_id, &next_team).await?;
+    if game.turns_remaining == Some(0) {
+        return end_game(state, game_id, None).await;
+    }
+    let game = start_turn_timer(state.clone(), game_id).await?;
+    let time_banks = current_time_banks(state.clone(), &game).await?;
+
+    let state = state.read().await;
+    state
+        .io
+        .to(game.room_id.to_string())
+        .emit("turn-changed", &game)
+        .ok();
+    if !time_banks.is_empty() {
+        state
+            .io
+            .to(game.room_id.to_string())
+            .emit("time-banks-updated", &time_banks)
+            .ok();
+    }
+    Ok(game)
+}
+
+/// Knocks `team` out of the game after it hit an assassin or ran out of
+/// time. The last team left wins, otherwise the others play on.
+pub async fn eliminate_team(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    team: &Team,
+) -> Result<Game, Box<dyn Error>> {
+    let game = add_eliminated_team(state.clone(), game_id, team).await?;
+    if let Some(winner) = game.last_team_standing() {
+        return end_game(state, game_id, Some(winner)).await;
+    }
+    state
+        .read()
+        .await
+        .io
+        .to(game.room_id.to_string())
+        .emit("team-eliminated", &game)
+        .ok();
+    if game.current_team == *team {
+        return end_turn(state, game_id).await;
+    }
+    Ok(game)
+}
+
+/// Finishes the game, scores it and tells the room about the result and the
+/// updated tally.
+pub async fn end_game(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    winner: Option<Team>,
+) -> Result<Game, Box<dyn Error>> {
+    let Some(game) = get_game_by_id(state.clone(), game_id).await? else {
+        return Err("Game not found".into());
+    };
+    let room = get_room_by_id(state.clone(), game.room_id).await?;
+    charge_time_bank(state.clone(), &game).await?;
+
+    let points = match &winner {
+        Some(Team::Green) => points_for_duet_win(&room.scoring_mode, game.turns_remaining),
+        Some(winner) => {
+            let fields = get_fields_for_game_id(state.clone(), game.id).await?;
+            points_for_win(&room.scoring_mode, &fields, winner)
+        }
+        None => 0,
+    };
+    let game = finish_game(state.clone(), game.id, winner.as_ref(), points).await?;
+    if let Some(winner) = &winner {
+        record_player_results(state.clone(), game.id, game.room_id, winner, points).await?;
+    }
+    let score = get_room_score(state.clone(), game.room_id).await?;
+    let summary = game_summary(state.clone(), game.clone()).await?;
+
+    let state = state.read().await;
+    state
+        .io
+        .to(game.room_id.to_string())
+        .emit("game-finished", &game)
+        .ok();
+    state
+        .io
+        .to(game.room_id.to_string())
+        .emit("score-updated", &score)
+        .ok();
+    state
+        .io
+        .to(game.room_id.to_string())
+        .emit("game-summary", &summary)
+        .ok();
+    Ok(game)
+}
+
+/// The game together with how the players reacted during it.
+pub async fn game_summary(
+    state: Arc<RwLock<MyState>>,
+    game: Game,
+) -> Result<GameSummary, Box<dyn Error>> {
+    let reactions = get_reaction_counts_for_game(state, game.id).await?;
+    Ok(GameSummary { game, reactions })
+}
+
+/// Drops the votes cast for the game's cards and tells the room, if there were any.
+async fn clear_field_votes(state: Arc<RwLock<MyState>>, game: &Game) -> Result<(), Box<dyn Error>> {
+    if delete_field_votes_for_game(state.clone(), game.id).await? > 0 {
+        state
+            .read()
+            .await
+            .io
+            .to(game.room_id.to_string())
+            .emit("votes-updated", Vec::<FieldVote>::new())
+            .ok();
+    }
+    Ok(())
+}
+
+/// The Socket.IO room of a single player's sockets, for events that only some
+/// of the players in a game room may get.
+pub fn player_room(player_id: i32) -> String {
+    format!("player-{player_id}")
+}
+
+/// Sends `team`'s marks on the game's cards to that team's players only.
+pub async fn emit_team_markers(
+    state: Arc<RwLock<MyState>>,
+    game: &Game,
+    team: &Team,
+) -> Result<(), Box<dyn Error>> {
+    let markers = get_field_markers_for_team(state.clone(), game.id, team).await?;
+    let teammates = get_players_by_room_id(state.clone(), game.room_id)
+        .await?
+        .into_iter()
+        .filter(|player| player.team == *team)
+        .map(|player| player_room(player.id))
+        .collect::<Vec<_>>();
+    if !teammates.is_empty() {
+        state
+            .read()
+            .await
+            .io
+            .to(teammates)
+            .emit("markers-updated", &markers)
+            .ok();
+    }
+    Ok(())
+}
+
+/// Drops the marks on the game's cards and tells each team that had any.
+async fn clear_field_markers(
+    state: Arc<RwLock<MyState>>,
+    game: &Game,
+) -> Result<(), Box<dyn Error>> {
+    let teams = delete_field_markers_for_game(state.clone(), game.id).await?;
+    for team in teams {
+        emit_team_markers(state.clone(), game, &team).await?;
+    }
+    Ok(())
+}
+
+/// Turns up a card the current guessers picked and plays out what it means:
+/// a win, a team knocked out, the next guess or the end of the turn. Returns
+/// false, without doing anything, when another guess turned the card up first.
+pub async fn reveal_field(
+    state: Arc<RwLock<MyState>>,
+    room: &Room,
+    game: &Game,
+    field: &Field,
+) -> Result<bool, Box<dyn Error>> {
+    if room.game_mode == GameMode::Duet {
+        return reveal_duet_field(state, game, field).await;
+    }
+    if !mark_field_as_used(state.clone(), field.id).await? {
+        return Ok(false);
+    }
+    clear_field_votes(state.clone(), game).await?;
+
+    // The team that hits an assassin is out, and with two teams that loses the game
+    if field.team == Team::Black {
+        eliminate_team(state, game.id, &game.current_team).await?;
+        return Ok(true);
+    }
+    let fields = get_fields_for_game_id(state.clone(), game.id).await?;
+    if let Some(winner) = winner_after_reveal(&fields, &game.teams_in_play()) {
+        end_game(state, game.id, Some(winner)).await?;
+        return Ok(true);
+    }
+
+    // A miss ends the turn, a hit uses up one of the clue's guesses
+    let hit = field.team == game.current_team;
+    let guesses_remaining = if hit {
+        use_guess(state.clone(), game.id).await?.guesses_remaining
+    } else {
+        game.guesses_remaining
+    };
+    if !hit || guesses_remaining == Some(0) {
+        end_turn(state, game.id).await?;
+    }
+    Ok(true)
+}
+
+/// Turns up a card of a Duet board, checked against the key of the side that
+/// gave the clue. A green card lets the guesser go on and an assassin loses
+/// the game. A bystander ends the turn but stays open for the other side's
+/// clues, until it turns out to be a bystander for both.
+pub async fn reveal_duet_field(
+    state: Arc<RwLock<MyState>>,
+    game: &Game,
+    field: &Field,
+) -> Result<bool, Box<dyn Error>> {
+    let reveal = duet_reveal(field, &game.current_team);
+    let revealed = match reveal {
+        DuetReveal::Bystander => {
+            mark_field_as_neutral_for(state.clone(), field.id, &game.current_team).await?
+        }
+        _ => mark_field_as_used(state.clone(), field.id).await?,
+    };
+    if !revealed {
+        return Ok(false);
+    }
+    clear_field_votes(state.clone(), game).await?;
+    match reveal {
+        DuetReveal::Green => {
+            let fields = get_fields_for_game_id(state.clone(), game.id).await?;
+            if duet_board_cleared(&fields) {
+                end_game(state, game.id, Some(Team::Green)).await?;
+            }
+        }
+        DuetReveal::Assassin => {
+            end_game(state, game.id, None).await?;
+        }
+        DuetReveal::Bystander | DuetReveal::BystanderForBoth => {
+            end_turn(state, game.id).await?;
+        }
+    }
+    Ok(true)
+}
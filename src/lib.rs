@@ -1,5 +1,15 @@
+pub mod board;
+pub mod chat;
+pub mod clock;
+pub mod game_flow;
+pub mod messages;
 pub mod models;
 pub mod my_state;
+pub mod normalize;
+pub mod presence;
+pub mod profanity;
 pub mod repositories;
+pub mod rules;
+pub mod turn_timer;
 pub mod types;
-pub mod words;
+pub mod word_packs;
@@ -1,30 +1,72 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use agenci::{
-    models::{Field, Player, Room, Team},
+    board::{teams_for_count, MAX_BOARD_SIDE},
+    chat::{chat_history_for_player, send_chat_message, send_reaction},
+    clock::SystemClock,
+    game_flow::{
+        draw_new_game, emit_team_markers, end_turn, game_summary, get_room_score, player_room,
+        reveal_field, start_new_game, store_new_game, word_packs_for_settings,
+    },
+    messages::player_joined,
+    models::{CommunityWordPack, Field, Game, Player, Room, Team},
     my_state::MyState,
+    normalize::{is_in_alphabet, normalize_word},
+    presence::{player_connected, player_disconnected, players_with_presence, socket_player},
+    profanity::{load_blocklists, DEFAULT_BLOCKLISTS_DIR},
     repositories::{
-        field_repository::{get_all_fields, get_field_by_id, get_fields_for_room_id, mark_field_as_used},
+        clue_repository::{create_clue, get_clues_for_game},
+        custom_word_repository::{get_custom_words_for_room, replace_custom_words_for_room},
+        field_repository::{
+            get_field_by_id, get_fields_for_game_id,
+        },
+        game_repository::{
+            advance_game_stage, delete_game, get_current_game_for_room,
+            get_finished_games_for_room, get_game_by_id, start_guessing,
+        },
+        marker_repository::{delete_field_marker, get_field_markers_for_team, set_field_marker},
         player_repository::{
-            create_player_for_the_room_id, get_player_by_id, get_players_by_room_id,
-            is_player_id_in_room,
-        }, room_repository::get_room_by_id,
+            create_player_for_the_room_id, create_spectator_for_the_room_id, get_player_by_id,
+            get_players_by_room_id, get_spectators_by_room_id,
+            get_room_host, is_player_id_in_room, rotate_showers_in_room, swap_player_teams_in_room,
+        }, room_repository::{create_room, get_room_by_id, get_rooms, update_room_settings},
+        vote_repository::{delete_field_vote, get_field_votes_for_game, set_field_vote},
+        word_pack_repository::{
+            create_community_word_pack, delete_community_word_pack,
+            get_approved_community_word_pack_summaries, get_community_word_pack_by_id,
+            get_community_word_pack_words, get_community_word_packs_by_status,
+            increment_community_word_pack_usage, set_community_word_pack_status,
+            update_community_word_pack,
+        },
+    },
+    rules::{
+        clue_violation, consensus_voters, field_for_onlooker, field_for_player, guesses_for_clue,
+        spectators_see_key, votes_needed,
+    },
+    turn_timer::{current_time_banks, restore_turn_timers, start_turn_timer},
+    types::{
+        ChatMessageRequest, CommunityWordPackWithWords, CreatedWordPack, ForkWordPackRequest, GameMode, GameStage,
+        GiveClueRequest, JoinRoomRequest, Language, LanguageFilter, MarkerRequest, ReactionRequest, RematchRequest, RevealMode, Role,
+        RoomSettings, RoomSnapshot, TurnPhase, WordPackRequest, WordPackStatus,
+    },
+    word_packs::{
+        load_word_packs, parse_community_word_pack_key, validate_community_word_pack,
+        validate_custom_words, DEFAULT_WORD_PACKS_DIR,
     },
-    types::{JoinRoomRequest, Role},
-    words::WORDS,
 };
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use axum_macros::debug_handler;
-use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rand::{distributions::Alphanumeric, Rng};
 use serde_json::{json, Value};
 use socketioxide::{
-    extract::{Bin, Data, SocketRef},
+    extract::{Data, SocketRef},
     SocketIo,
 };
 use sqlx::PgPool;
@@ -37,20 +79,477 @@ async fn hello_world() -> &'static str {
     "Hello, chuju!"
 }
 
-async fn check_field_handler(state: State<Arc<RwLock<MyState>>>, Path((field_id, player_id)): Path<(i32, i32)>) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let player = get_player_by_id(state.clone().0, player_id)
+/// Loads what a guess at `field_id` by `player_id` needs and checks that the
+/// player may make it now.
+async fn load_guess(
+    state: &Arc<RwLock<MyState>>,
+    field_id: i32,
+    player_id: i32,
+) -> Result<(Player, Field, Game, Room), (StatusCode, String)> {
+    let player = get_player_by_id(state.clone(), player_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?.map_or_else(|| Err((StatusCode::NOT_FOUND, "Player not found".to_string())), |p| Ok(p))?;
-    let field = get_field_by_id(state.clone().0, field_id)
+    let field = get_field_by_id(state.clone(), field_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?.map_or_else(|| Err((StatusCode::NOT_FOUND, "Field not found".to_string())), |f| Ok(f))?;
-    if player.role != Role::Guesser {
-        return Err((StatusCode::FORBIDDEN, "Only guessers can check fields".to_string()));
+    if player.role == Role::Spectator {
+        return Err((StatusCode::FORBIDDEN, "Spectators cannot play".to_string()));
+    }
+    let game = get_game_by_id(state.clone(), field.game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    if matches!(game.game_stage, GameStage::Finished) {
+        return Err((StatusCode::CONFLICT, "Game is already finished".to_string()));
+    }
+    if player.room_id != game.room_id {
+        return Err((StatusCode::FORBIDDEN, "Player is not in this game's room".to_string()));
+    }
+    let room = get_room_by_id(state.clone(), game.room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    match room.game_mode {
+        GameMode::Classic => {
+            if player.role != Role::Guesser {
+                return Err((StatusCode::FORBIDDEN, "Only guessers can check fields".to_string()));
+            }
+            if player.team != game.current_team {
+                return Err((StatusCode::FORBIDDEN, "It is not your team's turn".to_string()));
+            }
+        }
+        // In Duet the side that did not give the clue does the guessing
+        GameMode::Duet => {
+            if player.team == game.current_team {
+                return Err((StatusCode::FORBIDDEN, "Your partner guesses this turn".to_string()));
+            }
+        }
+    }
+    if game.turn_phase != TurnPhase::Guess {
+        return Err((StatusCode::CONFLICT, "Wait for your shower's clue".to_string()));
+    }
+    if field.is_used {
+        return Err((StatusCode::CONFLICT, "Field is already revealed".to_string()));
+    }
+    if field.neutral_side().as_ref() == Some(&game.current_team) {
+        return Err((StatusCode::CONFLICT, "Field is already a bystander for this clue".to_string()));
+    }
+    Ok((player, field, game, room))
+}
+
+async fn check_field_handler(state: State<Arc<RwLock<MyState>>>, Path((field_id, player_id)): Path<(i32, i32)>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (_, field, game, room) = load_guess(&state, field_id, player_id).await?;
+    if room.reveal_mode == RevealMode::Consensus {
+        return Err((StatusCode::CONFLICT, "Cards are revealed by vote in this room".to_string()));
+    }
+    let revealed = reveal_field(state.0, &room, &game, &field)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !revealed {
+        return Err((StatusCode::CONFLICT, "Field is already revealed".to_string()));
     }
-    mark_field_as_used(state.0, field_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     Ok(StatusCode::OK)
 }
 
+/// Votes for a card in a consensus room, replacing the player's earlier vote.
+/// The card is revealed once enough of the guessers agree on it.
+async fn vote_for_field_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((field_id, player_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (player, field, game, room) = load_guess(&state, field_id, player_id).await?;
+    if room.reveal_mode != RevealMode::Consensus {
+        return Err((StatusCode::CONFLICT, "Cards are revealed right away in this room".to_string()));
+    }
+    set_field_vote(state.clone().0, game.id, field.id, player.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::CONFLICT, "Field is already revealed".to_string()))?;
+    let votes = get_field_votes_for_game(state.clone().0, game.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    state
+        .read()
+        .await
+        .io
+        .to(game.room_id.to_string())
+        .emit("votes-updated", &votes)
+        .ok();
+
+    let players = get_players_by_room_id(state.clone().0, game.room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let players = players_with_presence(state.clone().0, players).await;
+    let voters = consensus_voters(&players, &votes, &game, &room.game_mode);
+    let field_votes = votes.iter().filter(|vote| vote.field_id == field.id).count();
+    // Two last votes can land at once; only one of them turns the card up
+    if field_votes >= votes_needed(voters, room.consensus_percent) {
+        reveal_field(state.clone().0, &room, &game, &field)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    Ok((StatusCode::OK, Json(votes)))
+}
+
+async fn withdraw_vote_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((game_id, player_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let game = get_game_by_id(state.clone().0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    delete_field_vote(state.clone().0, game.id, player_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let votes = get_field_votes_for_game(state.clone().0, game.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    state
+        .read()
+        .await
+        .io
+        .to(game.room_id.to_string())
+        .emit("votes-updated", &votes)
+        .ok();
+    Ok((StatusCode::OK, Json(votes)))
+}
+
+/// Marks a card for the player's teammates without revealing it. Only the
+/// current guessers can mark cards, and only their team gets to see the marks.
+async fn mark_field_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((field_id, player_id)): Path<(i32, i32)>,
+    Json(request): Json<MarkerRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (player, field, game, _) = load_guess(&state, field_id, player_id).await?;
+    let marker = set_field_marker(state.clone().0, game.id, field.id, player.id, &player.team, &request.kind)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    emit_team_markers(state.clone().0, &game, &player.team)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(marker)))
+}
+
+async fn unmark_field_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((field_id, player_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let player = get_player_by_id(state.clone().0, player_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Player not found".to_string()))?;
+    let field = get_field_by_id(state.clone().0, field_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Field not found".to_string()))?;
+    let game = get_game_by_id(state.clone().0, field.game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    delete_field_marker(state.clone().0, field.id, player.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    emit_team_markers(state.clone().0, &game, &player.team)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::OK)
+}
+
+/// The marks the player's own team put on the game's cards.
+async fn get_markers_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((game_id, player_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let player = get_player_by_id(state.clone().0, player_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Player not found".to_string()))?;
+    let game = get_game_by_id(state.clone().0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    if player.room_id != game.room_id || player.role == Role::Spectator {
+        return Err((StatusCode::FORBIDDEN, "Player is not playing this game".to_string()));
+    }
+    let markers = get_field_markers_for_team(state.clone().0, game.id, &player.team)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(markers)))
+}
+
+/// The summary of a finished game, with the reactions sent during it.
+async fn get_game_summary_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(game_id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let game = get_game_by_id(state.clone().0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    if !matches!(game.game_stage, GameStage::Finished) {
+        return Err((StatusCode::CONFLICT, "Game is not finished yet".to_string()));
+    }
+    let summary = game_summary(state.0, game)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(summary)))
+}
+
+async fn give_clue_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((game_id, player_id)): Path<(i32, i32)>,
+    Json(request): Json<GiveClueRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let player = get_player_by_id(state.clone().0, player_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Player not found".to_string()))?;
+    if player.role == Role::Spectator {
+        return Err((StatusCode::FORBIDDEN, "Spectators cannot play".to_string()));
+    }
+    let game = get_game_by_id(state.clone().0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    if player.room_id != game.room_id {
+        return Err((StatusCode::FORBIDDEN, "Player is not in this game's room".to_string()));
+    }
+    let room = get_room_by_id(state.clone().0, game.room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    // Duet players take turns giving clues, whatever their role
+    if room.game_mode == GameMode::Classic && player.role != Role::Shower {
+        return Err((StatusCode::FORBIDDEN, "Only showers can give clues".to_string()));
+    }
+    if player.team != game.current_team {
+        return Err((StatusCode::FORBIDDEN, "It is not your turn to give a clue".to_string()));
+    }
+    if matches!(game.game_stage, GameStage::Finished) {
+        return Err((StatusCode::CONFLICT, "Game is already finished".to_string()));
+    }
+
+    let word = normalize_word(&request.word, &room.language);
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        return Err((StatusCode::BAD_REQUEST, "Clue must be a single word".to_string()));
+    }
+    if !is_in_alphabet(&word, &room.language) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Clue must only use letters of the {} alphabet", room.language.to_string()),
+        ));
+    }
+    if game.turn_phase != TurnPhase::Clue {
+        return Err((StatusCode::CONFLICT, "Your team already has a clue this turn".to_string()));
+    }
+    if request.number < 0 {
+        return Err((StatusCode::BAD_REQUEST, "Clue number cannot be negative".to_string()));
+    }
+    let fields = get_fields_for_game_id(state.clone().0, game.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let cards_left = fields
+        .iter()
+        .filter(|field| !field.is_used && field.team == player.team)
+        .count() as i32;
+    let guesses = match room.game_mode {
+        GameMode::Classic => guesses_for_clue(
+            request.number,
+            cards_left,
+            request.unlimited,
+            &room.zero_clue_rule,
+            room.allow_unlimited_clues,
+        )
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+        // Duet guessers go on until they miss or stop
+        GameMode::Duet => None,
+    };
+    if let Some(violation) = clue_violation(&word, &fields, &room.clue_strictness, &room.language) {
+        return Err((StatusCode::BAD_REQUEST, violation));
+    }
+
+    if matches!(game.game_stage, GameStage::WaitingForPlayers) {
+        let now = state.read().await.clock.now();
+        advance_game_stage(state.clone().0, game.id, now)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        // The first turn's clock starts once the game is under way
+        start_turn_timer(state.clone().0, game.id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    let clue = create_clue(
+        state.clone().0,
+        game.id,
+        player.id,
+        &player.team,
+        word,
+        request.number,
+        request.unlimited,
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    start_guessing(state.clone().0, game.id, guesses)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    state
+        .read()
+        .await
+        .io
+        .to(game.room_id.to_string())
+        .emit("clue-given", &clue)
+        .ok();
+
+    Ok((StatusCode::CREATED, Json(clue)))
+}
+
+async fn end_turn_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((game_id, player_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let player = get_player_by_id(state.clone().0, player_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Player not found".to_string()))?;
+    if player.role == Role::Spectator {
+        return Err((StatusCode::FORBIDDEN, "Spectators cannot play".to_string()));
+    }
+    let game = get_game_by_id(state.clone().0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    if player.room_id != game.room_id {
+        return Err((StatusCode::FORBIDDEN, "Player is not in this game's room".to_string()));
+    }
+    let room = get_room_by_id(state.clone().0, game.room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    match room.game_mode {
+        GameMode::Classic => {
+            if player.role != Role::Guesser {
+                return Err((StatusCode::FORBIDDEN, "Only guessers can end the turn".to_string()));
+            }
+            if player.team != game.current_team {
+                return Err((StatusCode::FORBIDDEN, "It is not your team's turn".to_string()));
+            }
+        }
+        GameMode::Duet => {
+            if player.team == game.current_team {
+                return Err((StatusCode::FORBIDDEN, "Your partner guesses this turn".to_string()));
+            }
+        }
+    }
+    if matches!(game.game_stage, GameStage::Finished) {
+        return Err((StatusCode::CONFLICT, "Game is already finished".to_string()));
+    }
+    if game.turn_phase != TurnPhase::Guess {
+        return Err((StatusCode::CONFLICT, "Wait for your shower's clue".to_string()));
+    }
+
+    let game = end_turn(state.0, game.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(game)))
+}
+
+async fn get_current_game_for_room_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(room_id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let game = get_current_game_for_room(state.0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    Ok((StatusCode::OK, Json(game)))
+}
+
+async fn get_finished_games_for_room_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(room_id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let games = get_finished_games_for_room(state.0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(games)))
+}
+
+async fn get_game_by_id_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(game_id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let game = get_game_by_id(state.0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    Ok((StatusCode::OK, Json(game)))
+}
+
+/// The board as anyone may see it: the key stays hidden until the game is
+/// over, whatever the room's spectator delay. Players get their view, and
+/// spectators the delayed key, from `/game/:game_id/player/:player_id/fields`.
+async fn get_fields_for_game_id_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(game_id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let game = get_game_by_id(state.clone().0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    let room = get_room_by_id(state.clone().0, game.room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let fields = get_fields_for_game_id(state.0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let full_key = matches!(game.game_stage, GameStage::Finished);
+    let fields = fields
+        .iter()
+        .map(|field| field_for_onlooker(field, &room.game_mode, full_key))
+        .collect::<Vec<_>>();
+    Ok((StatusCode::OK, Json(fields)))
+}
+
+async fn get_fields_for_player_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((game_id, player_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let player = get_player_by_id(state.clone().0, player_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Player not found".to_string()))?;
+    let game = get_game_by_id(state.clone().0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Game not found".to_string()))?;
+    if player.room_id != game.room_id {
+        return Err((StatusCode::FORBIDDEN, "Player is not in this game's room".to_string()));
+    }
+    let room = get_room_by_id(state.clone().0, game.room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let fields = get_fields_for_game_id(state.clone().0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let now = state.read().await.clock.now();
+    let full_key = matches!(game.game_stage, GameStage::Finished)
+        || (player.role == Role::Spectator && spectators_see_key(&room, &game, now));
+    let fields = fields
+        .iter()
+        .map(|field| field_for_player(field, &player, &room.game_mode, full_key))
+        .collect::<Vec<_>>();
+    Ok((StatusCode::OK, Json(fields)))
+}
+
+async fn get_clues_for_game_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(game_id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let clues = get_clues_for_game(state.0, game_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(clues)))
+}
+
 async fn get_room_by_room_id_handler(
     state: State<Arc<RwLock<MyState>>>,
     Path(room_id): Path<i32>,
@@ -61,23 +560,54 @@ async fn get_room_by_room_id_handler(
     Ok((StatusCode::OK, Json(room)))
 }
 
-async fn get_all_fields_handler(
+async fn get_room_snapshot_handler(
     state: State<Arc<RwLock<MyState>>>,
+    Path(room_id): Path<i32>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let fields = get_all_fields(state.0)
+    let room = get_room_by_id(state.clone().0, room_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    Ok((StatusCode::OK, Json(fields)))
+    let game = get_current_game_for_room(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let players = get_players_by_room_id(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let spectators = get_spectators_by_room_id(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let players = players_with_presence(state.clone().0, players).await;
+    let spectators = players_with_presence(state.clone().0, spectators).await;
+    let score = get_room_score(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let time_banks = match &game {
+        Some(game) => current_time_banks(state.0, game)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        None => Vec::new(),
+    };
+    Ok((
+        StatusCode::OK,
+        Json(RoomSnapshot {
+            room,
+            game,
+            players,
+            spectators,
+            score,
+            time_banks,
+        }),
+    ))
 }
 
-async fn get_fields_for_room_id_handler(
+async fn get_room_score_handler(
     state: State<Arc<RwLock<MyState>>>,
     Path(room_id): Path<i32>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let fields = get_fields_for_room_id(state.0, room_id)
+    let score = get_room_score(state.0, room_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    Ok((StatusCode::OK, Json(fields)))
+    Ok((StatusCode::OK, Json(score)))
 }
 
 async fn is_player_in_room_handler(
@@ -100,6 +630,23 @@ async fn create_player_for_the_room_id_handler(
     Ok((StatusCode::CREATED, Json(player)))
 }
 
+async fn create_spectator_for_the_room_id_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((username, room_id)): Path<(String, i32)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let spectator = create_spectator_for_the_room_id(state.clone().0, username, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    state
+        .read()
+        .await
+        .io
+        .to(room_id.to_string())
+        .emit("spectator-joined", &spectator)
+        .ok();
+    Ok((StatusCode::CREATED, Json(spectator)))
+}
+
 async fn get_player_by_id_handler(
     state: State<Arc<RwLock<MyState>>>,
     Path(player_id): Path<i32>,
@@ -115,83 +662,503 @@ async fn get_players_for_room_handler(
     state: State<Arc<RwLock<MyState>>>,
     Path(room_id): Path<i32>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let players = get_players_by_room_id(state.0, room_id)
+    let players = get_players_by_room_id(state.clone().0, room_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let players = players_with_presence(state.0, players).await;
     Ok((StatusCode::OK, Json(players)))
 }
 
 #[axum_macros::debug_handler]
 async fn add_room_handler(
     state: State<Arc<RwLock<MyState>>>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    let pool = &state.read().await.pool;
-    match sqlx::query_as!(Room, "INSERT INTO rooms DEFAULT VALUES RETURNING *")
-        .fetch_one(pool)
+    body: Bytes,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // No body means the default settings, a malformed one is an error
+    let mut settings = if body.is_empty() {
+        RoomSettings::default()
+    } else {
+        serde_json::from_slice::<RoomSettings>(&body)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+    };
+    let word_packs = word_packs_for_settings(state.clone().0, &settings)
         .await
-    {
-        Ok(room) => {
-            let words = WORDS.clone().map(|s| s.to_string());
-            assert!(words.len() >= 25);
-
-            let mut rng = StdRng::from_entropy();
-            let words = words.choose_multiple(&mut rng, 25).collect::<Vec<_>>();
-            let mut query = String::from("INSERT INTO fields (room_id, text, team) VALUES ");
-            let mut params: Vec<(i32, String, String)> = Vec::new();
-
-            // Create a vector of indices for all fields
-            let mut indices: Vec<usize> = (0..words.len()).collect();
-            let mut rng = StdRng::from_entropy();
-
-            // Shuffle indices to randomize selection
-            indices.shuffle(&mut rng);
-
-            // Get indices for each team
-            let red_indices = indices.iter().take(7).copied().collect::<Vec<_>>();
-            let blue_indices = indices.iter().skip(7).take(6).copied().collect::<Vec<_>>();
-            let black_index = indices.get(13).copied();
-            let neutral_indices = indices.iter().skip(14).copied().collect::<Vec<_>>();
-
-            for (i, word) in words.iter().enumerate() {
-                if i > 0 {
-                    query.push_str(", ");
-                }
-                query.push_str(&format!("(${}, ${}, ${})", i * 3 + 1, i * 3 + 2, i * 3 + 3));
-
-                let team = if red_indices.contains(&i) {
-                    "red"
-                } else if blue_indices.contains(&i) {
-                    "blue"
-                } else if black_index == Some(i) {
-                    "black"
-                } else {
-                    "neutral"
-                };
-
-                params.push((room.id, word.to_owned().clone(), team.to_string()));
-            }
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    settings
+        .resolve_language(&word_packs)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    settings
+        .validate(&word_packs)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
 
-            query.push_str(" ON CONFLICT DO NOTHING");
+    // The room only exists once its first board is stored too
+    let pool = state.read().await.pool.clone();
+    let mut transaction = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let room = create_room(&mut transaction)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let room = update_room_settings(&mut transaction, room.id, &settings)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let custom_words = match &settings.custom_words {
+        Some(words) => Some(
+            validate_custom_words(words, &room.language, &state.read().await.blocklists)
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+        ),
+        None => None,
+    };
+    if let Some(words) = &custom_words {
+        replace_custom_words_for_room(&mut transaction, room.id, words)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    if let Some(word_pack_id) = parse_community_word_pack_key(&room.word_pack) {
+        increment_community_word_pack_usage(&mut transaction, word_pack_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    let custom_words = custom_words.unwrap_or_default();
+    let new_game = draw_new_game(
+        state.0,
+        &room,
+        &custom_words,
+        settings.seed,
+        settings.starting_team.as_ref(),
+    )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    store_new_game(&mut transaction, &room, new_game)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    transaction
+        .commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-            let mut query_builder = sqlx::query(&query);
+    Ok((StatusCode::CREATED, Json(room)))
+}
 
-            for (room_id, text, team) in params {
-                query_builder = query_builder.bind(room_id).bind(text).bind(team);
-            }
+async fn update_room_settings_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path((room_id, player_id)): Path<(i32, i32)>,
+    Json(mut settings): Json<RoomSettings>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let host = get_room_host(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if host.map(|host| host.id) != Some(player_id) {
+        return Err((StatusCode::FORBIDDEN, "Only the host can change room settings".to_string()));
+    }
+    let game = get_current_game_for_room(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if let Some(game) = &game {
+        if !matches!(game.game_stage, GameStage::WaitingForPlayers) {
+            return Err((StatusCode::CONFLICT, "Settings can only be changed in the lobby".to_string()));
+        }
+    }
+    let previous_room = get_room_by_id(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let word_packs = word_packs_for_settings(state.clone().0, &settings)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    settings
+        .resolve_language(&word_packs)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    // A change may only clash with a setting that is not part of the request
+    settings
+        .merged_with(&previous_room)
+        .validate(&word_packs)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let language = settings.language.as_ref().unwrap_or(&previous_room.language);
+    let custom_words = match &settings.custom_words {
+        Some(words) => Some(
+            validate_custom_words(words, language, &state.read().await.blocklists)
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+        ),
+        None => None,
+    };
+    let pool = state.read().await.pool.clone();
+    let mut transaction = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let room = update_room_settings(&mut transaction, room_id, &settings)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if let Some(words) = &custom_words {
+        replace_custom_words_for_room(&mut transaction, room_id, words)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    if room.word_pack != previous_room.word_pack {
+        if let Some(word_pack_id) = parse_community_word_pack_key(&room.word_pack) {
+            increment_community_word_pack_usage(&mut transaction, word_pack_id)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        }
+    }
 
-            query_builder
-                .execute(pool)
+    // The lobby board has not been played yet, so redraw it with the new
+    // settings. It keeps its starting team unless a seed picks one.
+    let lobby_team = match game {
+        Some(game) => {
+            delete_game(&mut transaction, game.id)
                 .await
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            Some(game.starting_team)
+        }
+        None => None,
+    };
+    let starting_team = match (&settings.starting_team, settings.seed) {
+        (Some(team), _) => Some(team.clone()),
+        (None, Some(_)) => None,
+        (None, None) => lobby_team,
+    };
+    let custom_words = match custom_words {
+        Some(words) => words,
+        None => get_custom_words_for_room(state.clone().0, room_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    };
+    let new_game = draw_new_game(
+        state.clone().0,
+        &room,
+        &custom_words,
+        settings.seed,
+        starting_team.as_ref(),
+    )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let game = store_new_game(&mut transaction, &room, new_game)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    transaction
+        .commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let io = state.read().await.io.clone();
+    io.to(room_id.to_string()).emit("room-settings-updated", &room).ok();
+    io.to(room_id.to_string()).emit("board-updated", &game).ok();
+
+    Ok((StatusCode::OK, Json(room)))
+}
 
-            Ok((StatusCode::CREATED, Json(room)))
+async fn rematch_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(room_id): Path<i32>,
+    request: Option<Json<RematchRequest>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let room = get_room_by_id(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let previous_game = get_current_game_for_room(state.clone().0, room_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if let Some(game) = &previous_game {
+        if !matches!(game.game_stage, GameStage::Finished) {
+            return Err((StatusCode::CONFLICT, "Game is not finished yet".to_string()));
         }
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+    // The team after the previous starting one opens the rematch, unless the
+    // request replays a seed
+    let starting_team = match (&request.starting_team, request.seed) {
+        (Some(team), _) => Some(team.clone()),
+        (None, Some(_)) => None,
+        (None, None) => previous_game.and_then(|game| game.team_order().get(1).cloned()),
+    };
+
+    if request.swap_colors {
+        let teams = match room.game_mode {
+            GameMode::Classic => teams_for_count(room.team_count),
+            GameMode::Duet => teams_for_count(2),
+        };
+        swap_player_teams_in_room(state.clone().0, room_id, &teams)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    if request.rotate_showers {
+        rotate_showers_in_room(state.clone().0, room_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    let game = start_new_game(state.clone().0, &room, request.seed, starting_team.as_ref())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    state
+        .read()
+        .await
+        .io
+        .to(room_id.to_string())
+        .emit("rematch", &game)
+        .ok();
+
+    Ok((StatusCode::CREATED, Json(game)))
+}
+
+async fn get_word_packs_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Query(filter): Query<LanguageFilter>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // Approved community packs come first, most used at the top
+    let mut packs = get_approved_community_word_pack_summaries(state.clone().0, filter.language.as_ref())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    packs.extend(
+        state
+            .read()
+            .await
+            .word_packs
+            .iter()
+            .filter(|pack| filter.language.as_ref().is_none_or(|language| pack.language == *language))
+            .map(|pack| pack.summary()),
+    );
+    Ok((StatusCode::OK, Json(packs)))
+}
+
+const EDIT_TOKEN_HEADER: &str = "x-edit-token";
+const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+fn header_matches(headers: &HeaderMap, name: &str, expected: &str) -> bool {
+    headers.get(name).and_then(|value| value.to_str().ok()) == Some(expected)
+}
+
+fn generate_edit_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+async fn require_admin(
+    state: &Arc<RwLock<MyState>>,
+    headers: &HeaderMap,
+) -> Result<(), (StatusCode, String)> {
+    match &state.read().await.admin_token {
+        Some(token) if header_matches(headers, ADMIN_TOKEN_HEADER, token) => Ok(()),
+        _ => Err((StatusCode::FORBIDDEN, "Admin token required".to_string())),
     }
 }
 
+/// Loads a community pack for its author, who proves ownership with the edit
+/// token handed out on creation.
+async fn get_owned_word_pack(
+    state: &Arc<RwLock<MyState>>,
+    word_pack_id: i32,
+    headers: &HeaderMap,
+) -> Result<CommunityWordPack, (StatusCode, String)> {
+    let word_pack = get_community_word_pack_by_id(state.clone(), word_pack_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Word pack not found".to_string()))?;
+    if !header_matches(headers, EDIT_TOKEN_HEADER, &word_pack.edit_token) {
+        return Err((StatusCode::FORBIDDEN, "Invalid edit token".to_string()));
+    }
+    Ok(word_pack)
+}
+
+async fn create_word_pack_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Json(mut request): Json<WordPackRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let words = validate_community_word_pack(
+        &request,
+        (MAX_BOARD_SIDE * MAX_BOARD_SIDE) as usize,
+        &state.read().await.blocklists,
+    )
+    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    request.name = request.name.trim().to_string();
+
+    let edit_token = generate_edit_token();
+    let word_pack = create_community_word_pack(state.0, &request, &words, &edit_token, None)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWordPack {
+            word_pack,
+            edit_token,
+        }),
+    ))
+}
+
+async fn get_word_pack_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(word_pack_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let word_pack = get_community_word_pack_by_id(state.clone().0, word_pack_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Word pack not found".to_string()))?;
+    // Unapproved packs are only visible to their author
+    if word_pack.status != WordPackStatus::Approved
+        && !header_matches(&headers, EDIT_TOKEN_HEADER, &word_pack.edit_token)
+    {
+        return Err((StatusCode::NOT_FOUND, "Word pack not found".to_string()));
+    }
+    let words = get_community_word_pack_words(state.0, word_pack_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(CommunityWordPackWithWords { word_pack, words })))
+}
+
+async fn update_word_pack_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(word_pack_id): Path<i32>,
+    headers: HeaderMap,
+    Json(mut request): Json<WordPackRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let word_pack = get_owned_word_pack(&state, word_pack_id, &headers).await?;
+    let words = validate_community_word_pack(
+        &request,
+        (MAX_BOARD_SIDE * MAX_BOARD_SIDE) as usize,
+        &state.read().await.blocklists,
+    )
+    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    request.name = request.name.trim().to_string();
+
+    // Published packs go back to moderation after every edit
+    let status = match word_pack.status {
+        WordPackStatus::Draft | WordPackStatus::Rejected => WordPackStatus::Draft,
+        WordPackStatus::Pending | WordPackStatus::Approved => WordPackStatus::Pending,
+    };
+    let word_pack = update_community_word_pack(state.0, word_pack_id, &request, &words, &status)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(word_pack)))
+}
+
+async fn delete_word_pack_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(word_pack_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    get_owned_word_pack(&state, word_pack_id, &headers).await?;
+    delete_community_word_pack(state.0, word_pack_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn fork_word_pack_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(word_pack_id): Path<i32>,
+    headers: HeaderMap,
+    Json(fork): Json<ForkWordPackRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let source = get_community_word_pack_by_id(state.clone().0, word_pack_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Word pack not found".to_string()))?;
+    if source.status != WordPackStatus::Approved
+        && !header_matches(&headers, EDIT_TOKEN_HEADER, &source.edit_token)
+    {
+        return Err((StatusCode::NOT_FOUND, "Word pack not found".to_string()));
+    }
+    if fork.author.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Word pack author cannot be empty".to_string()));
+    }
+    let words = get_community_word_pack_words(state.clone().0, word_pack_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let request = WordPackRequest {
+        name: source.name,
+        language: source.language,
+        tags: source.tags,
+        words: Vec::new(),
+        author: fork.author,
+    };
+    let edit_token = generate_edit_token();
+    let word_pack =
+        create_community_word_pack(state.0, &request, &words, &edit_token, Some(source.id))
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWordPack {
+            word_pack,
+            edit_token,
+        }),
+    ))
+}
+
+async fn publish_word_pack_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(word_pack_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let word_pack = get_owned_word_pack(&state, word_pack_id, &headers).await?;
+    if !matches!(word_pack.status, WordPackStatus::Draft | WordPackStatus::Rejected) {
+        return Err((StatusCode::CONFLICT, "Word pack is already published".to_string()));
+    }
+    let word_pack = set_community_word_pack_status(state.0, word_pack_id, &WordPackStatus::Pending)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(word_pack)))
+}
+
+async fn get_pending_word_packs_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    require_admin(&state, &headers).await?;
+    let word_packs = get_community_word_packs_by_status(state.0, &WordPackStatus::Pending)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::OK, Json(word_packs)))
+}
+
+async fn moderate_word_pack(
+    state: Arc<RwLock<MyState>>,
+    word_pack_id: i32,
+    headers: &HeaderMap,
+    status: WordPackStatus,
+) -> Result<CommunityWordPack, (StatusCode, String)> {
+    require_admin(&state, headers).await?;
+    let word_pack = get_community_word_pack_by_id(state.clone(), word_pack_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Word pack not found".to_string()))?;
+    if word_pack.status != WordPackStatus::Pending {
+        return Err((StatusCode::CONFLICT, "Word pack is not awaiting moderation".to_string()));
+    }
+    set_community_word_pack_status(state, word_pack_id, &status)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn approve_word_pack_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(word_pack_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let word_pack = moderate_word_pack(state.0, word_pack_id, &headers, WordPackStatus::Approved).await?;
+    Ok((StatusCode::OK, Json(word_pack)))
+}
+
+async fn reject_word_pack_handler(
+    state: State<Arc<RwLock<MyState>>>,
+    Path(word_pack_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let word_pack = moderate_word_pack(state.0, word_pack_id, &headers, WordPackStatus::Rejected).await?;
+    Ok((StatusCode::OK, Json(word_pack)))
+}
+
 async fn get_rooms_handler(
     state: State<Arc<RwLock<MyState>>>,
+    Query(filter): Query<LanguageFilter>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
     // let query = "SELECT * FROM rooms";
     // match sqlx::query_as::<_, Room>(query)
@@ -201,11 +1168,7 @@ async fn get_rooms_handler(
     //     Ok(rooms) => Ok(Json(rooms)),
     //     Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
     // }
-    let pool = &state.read().await.pool;
-    match sqlx::query_as!(Room, "SELECT * FROM rooms")
-        .fetch_all(pool)
-        .await
-    {
+    match get_rooms(state.0, filter.language.as_ref()).await {
         Ok(rooms) => Ok(Json(rooms)),
         Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
     }
@@ -215,41 +1178,90 @@ fn on_connect(socket: SocketRef, Data(data): Data<Value>, state: Arc<RwLock<MySt
     info!("Socket.IO connected: {:?} {:?}", socket.ns(), socket.id);
     socket.emit("auth", data).ok();
 
+    let disconnect_state = state.clone();
+    socket.on_disconnect(move |socket: SocketRef| {
+        player_disconnected(disconnect_state.clone(), socket.id)
+    });
+
+    let chat_state = state.clone();
+    socket.on(
+        "chat-message",
+        move |socket: SocketRef, Data::<ChatMessageRequest>(request)| {
+            let state = chat_state.clone();
+            async move {
+                let Some(player_id) = socket_player(&state, socket.id).await else {
+                    socket.emit("chat-error", "Join a room before chatting").ok();
+                    return;
+                };
+                if let Err(error) = send_chat_message(state, player_id, &request).await {
+                    socket.emit("chat-error", error).ok();
+                }
+            }
+        },
+    );
+
+    let reaction_state = state.clone();
     socket.on(
-        "message",
-        |socket: SocketRef, Data::<Value>(data), Bin(bin)| {
-            info!("Received event: {:?} {:?}", data, bin);
-            socket.emit("message-back", data).ok();
+        "react",
+        move |socket: SocketRef, Data::<ReactionRequest>(request)| {
+            let state = reaction_state.clone();
+            async move {
+                let Some(player_id) = socket_player(&state, socket.id).await else {
+                    socket.emit("reaction-error", "Join a room before reacting").ok();
+                    return;
+                };
+                if let Err(error) = send_reaction(state, player_id, &request).await {
+                    socket.emit("reaction-error", error).ok();
+                }
+            }
         },
     );
 
+    let join_state = state.clone();
     socket.on(
         "join-room",
-        |socket: SocketRef, Data::<JoinRoomRequest>(join_room_request)| {
-            let JoinRoomRequest {
-                player_id,
-                room_id,
-                username,
-            } = join_room_request;
-
-            info!("Player with id {} is joining room: {}", player_id, room_id);
-
-            socket.join(room_id.to_string()).ok();
-            let rooms = socket.rooms().unwrap();
-            let room = rooms.get(0).unwrap();
-            socket
-                .to(room.to_string())
-                .broadcast()
-                .emit(
-                    "player-joined",
-                    format!("{username} dołączył(a) do pokoju!"),
-                )
-                .ok();
+        move |socket: SocketRef, Data::<JoinRoomRequest>(join_room_request)| {
+            let state = join_state.clone();
+            async move {
+                let JoinRoomRequest {
+                    player_id,
+                    room_id,
+                    username,
+                } = join_room_request;
+
+                info!("Player with id {} is joining room: {}", player_id, room_id);
+
+                // The socket speaks for this player from now on, so it has to be in the room
+                let player = get_player_by_id(state.clone(), player_id).await.ok().flatten();
+                let Some(player) = player.filter(|player| player.room_id == room_id) else {
+                    socket.emit("join-error", "Player is not in this room").ok();
+                    return;
+                };
+                let language = get_room_by_id(state.clone(), room_id)
+                    .await
+                    .map(|room| room.language)
+                    .unwrap_or(Language::Polish);
+                socket.leave_all().ok();
+                socket.join(room_id.to_string()).ok();
+                socket.join(player_room(player_id)).ok();
+                if let Ok(history) = chat_history_for_player(state.clone(), &player).await {
+                    socket.emit("chat-history", history).ok();
+                }
+                player_connected(state.clone(), socket.id, room_id, player_id).await;
+                socket
+                    .to(room_id.to_string())
+                    .broadcast()
+                    .emit("player-joined", player_joined(&language, &username))
+                    .ok();
+            }
         },
     );
     socket.on("field-updated", |socket: SocketRef| {
         let rooms = socket.rooms().unwrap();
-        let room = rooms.get(0).unwrap();
+        // The socket is also in its player's own room, the game room is the numeric one
+        let Some(room) = rooms.iter().find(|room| room.parse::<i32>().is_ok()) else {
+            return;
+        };
         socket
             .to(room.to_string())
             .broadcast()
@@ -263,13 +1275,49 @@ fn on_connect(socket: SocketRef, Data(data): Data<Value>, state: Arc<RwLock<MySt
 }
 
 #[shuttle_runtime::main]
-async fn main(#[shuttle_shared_db::Postgres] pool: PgPool) -> shuttle_axum::ShuttleAxum {
+async fn main(
+    #[shuttle_shared_db::Postgres] pool: PgPool,
+    #[shuttle_runtime::Secrets] secrets: shuttle_runtime::SecretStore,
+) -> shuttle_axum::ShuttleAxum {
     sqlx::migrate!()
         .run(&pool)
         .await
         .expect("Failed to run migrations");
-    let state = MyState { pool };
+    let word_packs_dir =
+        std::env::var("WORD_PACKS_DIR").unwrap_or_else(|_| DEFAULT_WORD_PACKS_DIR.to_string());
+    let word_packs = load_word_packs(
+        std::path::Path::new(&word_packs_dir),
+        (MAX_BOARD_SIDE * MAX_BOARD_SIDE) as usize,
+    )
+    .expect("Failed to load word packs");
+    info!("Loaded {} word packs", word_packs.len());
+    let blocklists_dir =
+        std::env::var("BLOCKLISTS_DIR").unwrap_or_else(|_| DEFAULT_BLOCKLISTS_DIR.to_string());
+    let blocklists = load_blocklists(std::path::Path::new(&blocklists_dir))
+        .expect("Failed to load blocklists");
+    let admin_token = secrets.get("ADMIN_TOKEN");
+    if admin_token.is_none() {
+        info!("ADMIN_TOKEN secret is not set, word pack moderation is disabled");
+    }
+
+    let (layer, io) = SocketIo::new_layer();
+    let state = MyState {
+        pool,
+        io: io.clone(),
+        word_packs,
+        blocklists,
+        admin_token,
+        clock: Arc::new(SystemClock),
+        socket_players: HashMap::new(),
+        connected_players: HashMap::new(),
+        disconnected_players: HashMap::new(),
+        chat_rate_limits: HashMap::new(),
+        reaction_rate_limits: HashMap::new(),
+    };
     let state = Arc::new(RwLock::new(state));
+    restore_turn_timers(state.clone())
+        .await
+        .expect("Failed to restore turn timers");
 
     let results = sqlx::query_as!(Field, "SELECT * FROM fields")
         .fetch_all(&state.write().await.pool)
@@ -289,7 +1337,6 @@ async fn main(#[shuttle_shared_db::Postgres] pool: PgPool) -> shuttle_axum::Shut
         red_count, blue_count, black_count, neutral_count
     );
 
-    let (layer, io) = SocketIo::new_layer();
     let state_clone = state.clone();
     io.ns("/", move |socket, data| {
         on_connect(socket, data, state_clone.clone())
@@ -299,10 +1346,33 @@ async fn main(#[shuttle_shared_db::Postgres] pool: PgPool) -> shuttle_axum::Shut
         .route("/", get(hello_world))
         .route("/room", post(add_room_handler))
         .route("/room", get(get_rooms_handler))
-        .route("/fields", get(get_all_fields_handler))
+        .route("/word-packs", get(get_word_packs_handler))
+        .route("/word-packs/community", post(create_word_pack_handler))
+        .route("/word-packs/community/:word_pack_id", get(get_word_pack_handler))
+        .route("/word-packs/community/:word_pack_id", put(update_word_pack_handler))
+        .route("/word-packs/community/:word_pack_id", delete(delete_word_pack_handler))
+        .route("/word-packs/community/:word_pack_id/fork", post(fork_word_pack_handler))
+        .route("/word-packs/community/:word_pack_id/publish", post(publish_word_pack_handler))
+        .route("/admin/word-packs/pending", get(get_pending_word_packs_handler))
+        .route("/admin/word-packs/:word_pack_id/approve", post(approve_word_pack_handler))
+        .route("/admin/word-packs/:word_pack_id/reject", post(reject_word_pack_handler))
         .route("/room/:room_id/players", get(get_players_for_room_handler))
-        .route("/room/:room_id/fields", get(get_fields_for_room_id_handler))
         .route("/room/:room_id", get(get_room_by_room_id_handler))
+        .route("/room/:room_id/snapshot", get(get_room_snapshot_handler))
+        .route("/room/:room_id/score", get(get_room_score_handler))
+        .route("/room/:room_id/rematch", post(rematch_handler))
+        .route(
+            "/room/:room_id/player/:player_id/settings",
+            put(update_room_settings_handler),
+        )
+        .route("/room/:room_id/game", get(get_current_game_for_room_handler))
+        .route("/room/:room_id/games", get(get_finished_games_for_room_handler))
+        .route("/game/:game_id", get(get_game_by_id_handler))
+        .route("/game/:game_id/fields", get(get_fields_for_game_id_handler))
+        .route("/game/:game_id/clues", get(get_clues_for_game_handler))
+        .route("/game/:game_id/player/:player_id/fields", get(get_fields_for_player_handler))
+        .route("/game/:game_id/player/:player_id/clue", post(give_clue_handler))
+        .route("/game/:game_id/player/:player_id/end-turn", post(end_turn_handler))
         .route(
             "/is-player-in-room/:room_id/:player_id",
             get(is_player_in_room_handler),
@@ -311,8 +1381,20 @@ async fn main(#[shuttle_shared_db::Postgres] pool: PgPool) -> shuttle_axum::Shut
             "/player/:username/room/:room_id",
             post(create_player_for_the_room_id_handler),
         )
+        .route(
+            "/spectator/:username/room/:room_id",
+            post(create_spectator_for_the_room_id_handler),
+        )
         .route("/player/:player_id", get(get_player_by_id_handler))
         .route("/field/:field_id/player/:player_id", post(check_field_handler))
+        .route("/field/:field_id/player/:player_id/vote", post(vote_for_field_handler))
+        .route("/game/:game_id/player/:player_id/vote", delete(withdraw_vote_handler))
+        .route(
+            "/field/:field_id/player/:player_id/marker",
+            post(mark_field_handler).delete(unmark_field_handler),
+        )
+        .route("/game/:game_id/player/:player_id/markers", get(get_markers_handler))
+        .route("/game/:game_id/summary", get(get_game_summary_handler))
         .layer(
             ServiceBuilder::new()
                 .layer(CorsLayer::permissive())
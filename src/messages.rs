@@ -0,0 +1,9 @@
+use crate::types::Language;
+
+pub fn player_joined(language: &Language, username: &str) -> String {
+    match language {
+        Language::Polish => format!("{username} dołączył(a) do pokoju!"),
+        Language::English => format!("{username} joined the room!"),
+        Language::German => format!("{username} ist dem Raum beigetreten!"),
+    }
+}
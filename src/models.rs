@@ -1,26 +1,53 @@
+use std::ops::Deref;
+
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
-use crate::types::{GameStage, Role};
+use crate::types::{
+    ChatChannel, ClueStrictness, Difficulty, GameMode, GameStage, Language, MarkerKind,
+    ReactionKind, RevealMode, Role, ScoringMode, TurnPhase, WordPackStatus, ZeroClueRule,
+};
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Room {
     pub id: i32,
-    pub game_stage: GameStage,
-    pub current_team: Team,
+    pub board_rows: i32,
+    pub board_cols: i32,
+    pub scoring_mode: ScoringMode,
+    pub word_pack: String,
+    pub custom_word_ratio: i32,
+    pub language: Language,
+    pub avoid_recent_games: i32,
+    pub difficulty: Difficulty,
+    pub clue_strictness: ClueStrictness,
+    pub zero_clue_rule: ZeroClueRule,
+    pub allow_unlimited_clues: bool,
+    pub turn_seconds: i32,
+    pub time_bank_seconds: i32,
+    pub game_mode: GameMode,
+    pub team_count: i32,
+    pub spectators_see_key: bool,
+    pub spectator_key_delay_seconds: i32,
+    pub reveal_mode: RevealMode,
+    pub consensus_percent: i32,
+    pub mute_showers_while_guessing: bool,
     pub created_at: chrono::NaiveDateTime,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Team {
     #[serde(rename = "red")]
     Red,
     #[serde(rename = "blue")]
     Blue,
+    #[serde(rename = "yellow")]
+    Yellow,
     #[serde(rename = "neutral")]
     Neutral,
     #[serde(rename = "black")]
     Black,
+    #[serde(rename = "green")]
+    Green,
 }
 
 impl Team {
@@ -28,8 +55,20 @@ impl Team {
         match self {
             Team::Red => "red".to_string(),
             Team::Blue => "blue".to_string(),
+            Team::Yellow => "yellow".to_string(),
             Team::Neutral => "neutral".to_string(),
             Team::Black => "black".to_string(),
+            Team::Green => "green".to_string(),
+        }
+    }
+}
+
+impl Team {
+    pub fn opposite(&self) -> Team {
+        match self {
+            Team::Red => Team::Blue,
+            Team::Blue => Team::Red,
+            _ => Team::Red,
         }
     }
 }
@@ -39,21 +78,132 @@ impl From<String> for Team {
         match s.as_str() {
             "red" => Team::Red,
             "blue" => Team::Blue,
+            "yellow" => Team::Yellow,
             "neutral" => Team::Neutral,
             "black" => Team::Black,
+            "green" => Team::Green,
             _ => panic!("Invalid team"),
         }
     }
 }
 
+/// A list of teams kept in a text array column, decoded once when the row is
+/// read.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Teams(pub Vec<Team>);
+
+impl From<Vec<String>> for Teams {
+    fn from(teams: Vec<String>) -> Self {
+        Teams(teams.into_iter().map(Team::from).collect())
+    }
+}
+
+impl Deref for Teams {
+    type Target = [Team];
+
+    fn deref(&self) -> &[Team] {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Game {
+    pub id: i32,
+    pub room_id: i32,
+    pub game_stage: GameStage,
+    pub current_team: Team,
+    pub starting_team: Team,
+    pub winner: Option<String>,
+    pub points: i32,
+    pub board_rows: i32,
+    pub board_cols: i32,
+    pub seed: Option<i64>,
+    pub turn_phase: TurnPhase,
+    /// Guesses the current team has left this turn, `None` when unlimited.
+    pub guesses_remaining: Option<i32>,
+    /// Turns a Duet game has left, `None` for classic games.
+    pub turns_remaining: Option<i32>,
+    pub turn_deadline: Option<chrono::NaiveDateTime>,
+    /// The game's teams in turn order, see `team_order`.
+    pub teams: Teams,
+    pub eliminated_teams: Teams,
+    pub started_at: Option<chrono::NaiveDateTime>,
+    pub finished_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl Game {
+    pub fn winning_team(&self) -> Option<Team> {
+        self.winner.clone().map(Team::from)
+    }
+
+    /// The game's teams in turn order, starting with the team that went first.
+    pub fn team_order(&self) -> Vec<Team> {
+        self.teams.to_vec()
+    }
+
+    /// Teams that have not been knocked out of the game.
+    pub fn teams_in_play(&self) -> Vec<Team> {
+        self.team_order()
+            .into_iter()
+            .filter(|team| !self.eliminated_teams.contains(team))
+            .collect()
+    }
+
+    /// The winner once every other team has been knocked out.
+    pub fn last_team_standing(&self) -> Option<Team> {
+        match self.teams_in_play().as_slice() {
+            [winner] => Some(winner.clone()),
+            _ => None,
+        }
+    }
+
+    /// The team after the current one in turn order that is still in play.
+    pub fn next_team(&self) -> Team {
+        let teams = self.team_order();
+        let current = teams
+            .iter()
+            .position(|team| *team == self.current_team)
+            .unwrap_or(0);
+        let in_play = self.teams_in_play();
+        (1..=teams.len())
+            .map(|step| &teams[(current + step) % teams.len()])
+            .find(|team| in_play.contains(team))
+            .cloned()
+            .unwrap_or_else(|| self.current_team.opposite())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Field {
     pub id: i32,
-    pub room_id: i32,
+    pub game_id: i32,
     pub team: Team,
     pub text: String,
     pub is_used: bool,
+    pub position: i32,
+    pub row_index: i32,
+    pub col_index: i32,
     pub created_at: chrono::NaiveDateTime,
+    /// The blue side's color on a Duet board, where `team` is the red side's.
+    pub team_b: Option<String>,
+    /// The Duet side that already turned this card up as a bystander.
+    pub neutral_for: Option<String>,
+}
+
+impl Field {
+    /// The card's color on `side`'s key. Classic boards have one key for both.
+    pub fn key_for(&self, side: &Team) -> Team {
+        match (&self.team_b, side) {
+            (Some(team_b), Team::Blue) => Team::from(team_b.clone()),
+            _ => self.team.clone(),
+        }
+    }
+
+    pub fn neutral_side(&self) -> Option<Team> {
+        self.neutral_for.clone().map(Team::from)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -65,3 +215,105 @@ pub struct Player {
     pub role: Role,
     pub created_at: chrono::NaiveDateTime,
 }
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Clue {
+    pub id: i32,
+    pub game_id: i32,
+    pub player_id: i32,
+    pub team: Team,
+    pub word: String,
+    pub number: i32,
+    pub unlimited: bool,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TeamScore {
+    pub team: Team,
+    pub wins: i64,
+    pub points: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PlayerScore {
+    pub player_id: i32,
+    pub username: String,
+    pub wins: i64,
+    pub points: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct CommunityWordPack {
+    pub id: i32,
+    pub name: String,
+    pub language: Language,
+    pub tags: Vec<String>,
+    pub author: String,
+    #[serde(skip)]
+    pub edit_token: String,
+    pub status: WordPackStatus,
+    pub forked_from: Option<i32>,
+    pub usage_count: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TimeBank {
+    pub id: i32,
+    pub game_id: i32,
+    pub team: Team,
+    pub remaining_ms: i64,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct FieldVote {
+    pub id: i32,
+    pub game_id: i32,
+    pub field_id: i32,
+    pub player_id: i32,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct FieldMarker {
+    pub id: i32,
+    pub game_id: i32,
+    pub field_id: i32,
+    pub player_id: i32,
+    pub team: Team,
+    pub kind: MarkerKind,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ChatMessage {
+    pub id: i32,
+    pub room_id: i32,
+    pub player_id: i32,
+    pub username: String,
+    pub channel: ChatChannel,
+    pub team: Team,
+    pub text: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Reaction {
+    pub id: i32,
+    pub room_id: i32,
+    pub game_id: Option<i32>,
+    pub player_id: i32,
+    pub reaction: ReactionKind,
+    pub field_id: Option<i32>,
+    pub target_player_id: Option<i32>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ReactionCount {
+    pub reaction: ReactionKind,
+    pub count: i64,
+}
@@ -1,6 +1,54 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::NaiveDateTime;
+use socketioxide::{socket::Sid, SocketIo};
 use sqlx::PgPool;
 
+use crate::{clock::Clock, word_packs::WordPack};
+
 #[derive(Clone)]
 pub struct MyState {
     pub pool: PgPool,
+    pub io: SocketIo,
+    pub word_packs: Vec<WordPack>,
+    pub blocklists: HashMap<String, Vec<String>>,
+    pub admin_token: Option<String>,
+    pub clock: Arc<dyn Clock>,
+    /// The room and player each open socket joined as.
+    pub socket_players: HashMap<Sid, (i32, i32)>,
+    /// Open sockets per player that joined a room over Socket.IO.
+    pub connected_players: HashMap<i32, usize>,
+    /// When players whose sockets all closed lost their last one, until they
+    /// come back or the presence grace period makes them offline.
+    pub disconnected_players: HashMap<i32, NaiveDateTime>,
+    /// When each player sent their latest chat messages, for rate limiting.
+    pub chat_rate_limits: HashMap<i32, Vec<NaiveDateTime>>,
+    /// When each player sent their latest reactions, for throttling.
+    pub reaction_rate_limits: HashMap<i32, Vec<NaiveDateTime>>,
+}
+
+#[cfg(test)]
+impl MyState {
+    /// A state on `clock` whose pool never connects and whose sockets go
+    /// nowhere, for testing the in-memory bookkeeping.
+    pub fn for_tests(clock: Arc<dyn Clock>) -> Self {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/agenci")
+            .unwrap();
+        let (_, io) = SocketIo::builder().build_svc();
+        io.ns("/", || {});
+        MyState {
+            pool,
+            io,
+            word_packs: Vec::new(),
+            blocklists: HashMap::new(),
+            admin_token: None,
+            clock,
+            socket_players: HashMap::new(),
+            connected_players: HashMap::new(),
+            disconnected_players: HashMap::new(),
+            chat_rate_limits: HashMap::new(),
+            reaction_rate_limits: HashMap::new(),
+        }
+    }
 }
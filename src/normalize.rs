@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+
+use crate::types::Language;
+
+/// Trims and lowercases a word. German `ß` is spelled out as `ss`, which is
+/// how it is written in upper case and in Swiss German.
+pub fn normalize_word(word: &str, language: &Language) -> String {
+    let word = word.trim().to_lowercase();
+    match language {
+        Language::German => word.replace('ß', "ss"),
+        Language::Polish | Language::English => word,
+    }
+}
+
+/// Normalizes a word and strips its diacritics, so "zolw" matches "żółw" and
+/// "baer" matches "Bär".
+pub fn fold_diacritics(word: &str, language: &Language) -> String {
+    let word = normalize_word(word, language);
+    match language {
+        Language::Polish => word
+            .chars()
+            .map(|c| match c {
+                'ą' => 'a',
+                'ć' => 'c',
+                'ę' => 'e',
+                'ł' => 'l',
+                'ń' => 'n',
+                'ó' => 'o',
+                'ś' => 's',
+                'ź' | 'ż' => 'z',
+                c => c,
+            })
+            .collect(),
+        Language::German => word
+            .replace('ä', "ae")
+            .replace('ö', "oe")
+            .replace('ü', "ue"),
+        Language::English => word,
+    }
+}
+
+/// Checks that a normalized word only uses letters of the language's
+/// alphabet. Hyphens are allowed for compounds.
+pub fn is_in_alphabet(word: &str, language: &Language) -> bool {
+    let extra_letters = match language {
+        Language::Polish => "ąćęłńóśźż",
+        Language::German => "äöü",
+        Language::English => "",
+    };
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c == '-' || extra_letters.contains(c))
+}
+
+/// Rough stems of a word for spotting inflected forms, e.g. "pies" and "psa"
+/// or "Bär" and "Bären". Each word gets a few candidates (the folded word,
+/// the word with each matching ending cut off and, in Polish, without its
+/// fleeting "e"); two words share a stem when their candidates overlap.
+pub fn word_stems(word: &str, language: &Language) -> HashSet<String> {
+    let suffixes: &[&str] = match language {
+        Language::Polish => &[
+            "owie", "ami", "ach", "ego", "emu", "ymi", "imi", "ych", "ich", "owi", "iem", "om",
+            "em", "ow", "ie", "a", "e", "i", "y", "u", "o",
+        ],
+        Language::German => &["ern", "en", "er", "es", "em", "e", "n", "s"],
+        Language::English => &["ing", "ies", "ed", "es", "s", "y"],
+    };
+    let folded = fold_diacritics(word, language);
+    let mut stems = HashSet::from([folded.clone()]);
+    for suffix in suffixes {
+        if let Some(stem) = folded.strip_suffix(suffix) {
+            if is_long_enough_stem(stem, language) {
+                stems.insert(stem.to_string());
+            }
+        }
+    }
+    if *language == Language::Polish {
+        let fleeting = stems
+            .iter()
+            .filter_map(|stem| without_fleeting_e(stem))
+            .collect::<Vec<_>>();
+        stems.extend(fleeting);
+    }
+    stems
+}
+
+/// Stems need three letters to tell unrelated words apart. Polish also keeps
+/// two consonants, which is what a word without its fleeting "e" leaves
+/// ("psa" -> "ps" for "pies"); other two-letter stems match too much ("osa"
+/// and "oś").
+fn is_long_enough_stem(stem: &str, language: &Language) -> bool {
+    let is_consonant = |c: char| c.is_alphabetic() && !"aeiouy".contains(c);
+    match stem.chars().count() {
+        0 | 1 => false,
+        2 => *language == Language::Polish && stem.chars().all(is_consonant),
+        _ => true,
+    }
+}
+
+/// Drops the "e" or "ie" Polish loses when a word is inflected ("pies" -> "ps",
+/// "sen" -> "sn"), if the stem ends in consonant, e/ie, consonant.
+fn without_fleeting_e(stem: &str) -> Option<String> {
+    let is_vowel = |c: char| "aeiouy".contains(c);
+    let chars = stem.chars().collect::<Vec<_>>();
+    let [.., before, 'e', last] = chars[..] else {
+        return None;
+    };
+    if is_vowel(last) {
+        return None;
+    }
+    let cut = if before == 'i' { 2 } else { 1 };
+    let head = &chars[..chars.len() - 1 - cut];
+    if head.last().is_none_or(|c| is_vowel(*c)) {
+        return None;
+    }
+    Some(head.iter().chain([&last]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share_stem(a: &str, b: &str, language: &Language) -> bool {
+        !word_stems(a, language).is_disjoint(&word_stems(b, language))
+    }
+
+    #[test]
+    fn folds_case_and_diacritics() {
+        assert_eq!(fold_diacritics(" Żółw ", &Language::Polish), "zolw");
+        assert_eq!(fold_diacritics("ĄĆĘŁŃÓŚŹŻ", &Language::Polish), "acelnoszz");
+        assert_eq!(fold_diacritics("Bär", &Language::German), "baer");
+        assert_eq!(fold_diacritics("Straße", &Language::German), "strasse");
+        assert_eq!(fold_diacritics("Café", &Language::English), "café");
+    }
+
+    #[test]
+    fn drops_the_fleeting_e() {
+        assert_eq!(without_fleeting_e("pies").as_deref(), Some("ps"));
+        assert_eq!(without_fleeting_e("sen").as_deref(), Some("sn"));
+        assert_eq!(without_fleeting_e("lew").as_deref(), Some("lw"));
+        assert_eq!(without_fleeting_e("kociol").as_deref(), None);
+        assert_eq!(without_fleeting_e("dzien").as_deref(), Some("dzn"));
+        // A vowel around the "e" means it is not a fleeting one
+        assert_eq!(without_fleeting_e("mleko"), None);
+        assert_eq!(without_fleeting_e("aes"), None);
+        assert_eq!(without_fleeting_e("ser").as_deref(), Some("sr"));
+        assert_eq!(without_fleeting_e("e"), None);
+    }
+
+    #[test]
+    fn polish_inflections_share_a_stem() {
+        let pairs = [
+            ("pies", "psa"),
+            ("pies", "psy"),
+            ("sen", "snu"),
+            ("lew", "lwa"),
+            ("kot", "kota"),
+            ("kot", "kotami"),
+            ("dom", "domowi"),
+            ("żółw", "ŻÓŁWIE"),
+            ("żółw", "zolwiem"),
+            ("ryba", "ryby"),
+            ("miasto", "miastach"),
+        ];
+        for (word, form) in pairs {
+            assert!(share_stem(word, form, &Language::Polish), "{word} {form}");
+        }
+    }
+
+    #[test]
+    fn short_polish_words_do_not_share_a_stem() {
+        let pairs = [
+            ("osa", "oś"),
+            ("oko", "ok"),
+            ("pas", "pies"),
+            ("lato", "lot"),
+            ("sen", "syn"),
+            ("ryba", "ryż"),
+            ("ul", "ule"),
+            ("kos", "kot"),
+        ];
+        for (word, other) in pairs {
+            assert!(
+                !share_stem(word, other, &Language::Polish),
+                "{word} {other}"
+            );
+        }
+    }
+
+    #[test]
+    fn english_and_german_inflections_share_a_stem() {
+        for (word, form) in [("cat", "cats"), ("berry", "berries"), ("jump", "jumped")] {
+            assert!(share_stem(word, form, &Language::English), "{word} {form}");
+        }
+        for (word, form) in [("Bär", "Bären"), ("Hund", "Hunde"), ("Kind", "Kindern")] {
+            assert!(share_stem(word, form, &Language::German), "{word} {form}");
+        }
+    }
+
+    #[test]
+    fn english_and_german_near_misses_do_not_share_a_stem() {
+        for (word, other) in [("bus", "bush"), ("plan", "plane"), ("cart", "cat")] {
+            assert!(
+                !share_stem(word, other, &Language::English),
+                "{word} {other}"
+            );
+        }
+        for (word, other) in [("Rad", "Rat"), ("Hut", "Hütte"), ("Ende", "Ente")] {
+            assert!(
+                !share_stem(word, other, &Language::German),
+                "{word} {other}"
+            );
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+use socketioxide::socket::Sid;
+use tokio::sync::RwLock;
+
+use crate::{
+    models::Player,
+    my_state::MyState,
+    types::{PlayerWithPresence, Presence, PresenceUpdate},
+};
+
+/// How long a player whose sockets all closed stays away before they count
+/// as offline, so a page reload or a flaky connection goes unnoticed.
+pub const PRESENCE_GRACE_SECONDS: i64 = 30;
+
+pub fn player_presence(state: &MyState, player_id: i32) -> Presence {
+    if state.connected_players.contains_key(&player_id) {
+        Presence::Online
+    } else if state.disconnected_players.contains_key(&player_id) {
+        Presence::Away
+    } else {
+        Presence::Offline
+    }
+}
+
+pub async fn players_with_presence(
+    state: Arc<RwLock<MyState>>,
+    players: Vec<Player>,
+) -> Vec<PlayerWithPresence> {
+    let state = state.read().await;
+    players
+        .into_iter()
+        .map(|player| PlayerWithPresence {
+            presence: player_presence(&state, player.id),
+            player,
+        })
+        .collect()
+}
+
+/// Binds the socket to the player who joined a room on it and counts it as
+/// one of theirs; they are online from their first socket on. A socket that
+/// joins again as someone else stops counting for whoever it was before.
+pub async fn player_connected(state: Arc<RwLock<MyState>>, sid: Sid, room_id: i32, player_id: i32) {
+    let (left, was_online) = {
+        let mut state = state.write().await;
+        let previous = state.socket_players.insert(sid, (room_id, player_id));
+        if previous == Some((room_id, player_id)) {
+            return;
+        }
+        let left = previous.and_then(|(room_id, player_id)| {
+            forget_socket(&mut state, player_id).map(|at| (room_id, player_id, at))
+        });
+        state.disconnected_players.remove(&player_id);
+        let sockets = state.connected_players.entry(player_id).or_insert(0);
+        *sockets += 1;
+        (left, *sockets > 1)
+    };
+    if let Some((room_id, player_id, disconnected_at)) = left {
+        player_went_away(state.clone(), room_id, player_id, disconnected_at).await;
+    }
+    if !was_online {
+        emit_presence(&state, room_id, player_id, Presence::Online).await;
+    }
+}
+
+/// The player the socket joined a room as, if it did.
+pub async fn socket_player(state: &Arc<RwLock<MyState>>, sid: Sid) -> Option<i32> {
+    let state = state.read().await;
+    state
+        .socket_players
+        .get(&sid)
+        .map(|(_, player_id)| *player_id)
+}
+
+/// Forgets a closed socket. Once the last socket of its player is gone they
+/// are away, and offline if they do not come back within the grace period.
+pub async fn player_disconnected(state: Arc<RwLock<MyState>>, sid: Sid) {
+    let left = {
+        let mut state = state.write().await;
+        let Some((room_id, player_id)) = state.socket_players.remove(&sid) else {
+            return;
+        };
+        forget_socket(&mut state, player_id).map(|at| (room_id, player_id, at))
+    };
+    if let Some((room_id, player_id, disconnected_at)) = left {
+        player_went_away(state, room_id, player_id, disconnected_at).await;
+    }
+}
+
+/// Uncounts one socket of the player. Returns when they lost their last one.
+fn forget_socket(state: &mut MyState, player_id: i32) -> Option<NaiveDateTime> {
+    let sockets = state.connected_players.get_mut(&player_id)?;
+    *sockets -= 1;
+    if *sockets > 0 {
+        return None;
+    }
+    state.connected_players.remove(&player_id);
+    let now = state.clock.now();
+    state.disconnected_players.insert(player_id, now);
+    Some(now)
+}
+
+async fn player_went_away(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+    player_id: i32,
+    disconnected_at: NaiveDateTime,
+) {
+    emit_presence(&state, room_id, player_id, Presence::Away).await;
+
+    tokio::spawn(async move {
+        let clock = state.read().await.clock.clone();
+        clock
+            .sleep_until(disconnected_at + chrono::Duration::seconds(PRESENCE_GRACE_SECONDS))
+            .await;
+        if went_offline(&state, player_id, disconnected_at).await {
+            emit_presence(&state, room_id, player_id, Presence::Offline).await;
+        }
+    });
+}
+
+/// Marks the player offline unless they came back, or left again later,
+/// since `disconnected_at`. Their rate limit windows have run out by then
+/// too, so those are dropped with them.
+async fn went_offline(
+    state: &Arc<RwLock<MyState>>,
+    player_id: i32,
+    disconnected_at: NaiveDateTime,
+) -> bool {
+    let mut state = state.write().await;
+    if state.disconnected_players.get(&player_id) != Some(&disconnected_at) {
+        return false;
+    }
+    state.disconnected_players.remove(&player_id);
+    state.chat_rate_limits.remove(&player_id);
+    state.reaction_rate_limits.remove(&player_id);
+    true
+}
+
+async fn emit_presence(
+    state: &Arc<RwLock<MyState>>,
+    room_id: i32,
+    player_id: i32,
+    presence: Presence,
+) {
+    let update = PresenceUpdate {
+        player_id,
+        presence,
+    };
+    state
+        .read()
+        .await
+        .io
+        .to(room_id.to_string())
+        .emit("presence-updated", &update)
+        .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+
+    /// Lets the spawned presence timers run up to their next wait.
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    fn state(clock: &Arc<FakeClock>) -> Arc<RwLock<MyState>> {
+        Arc::new(RwLock::new(MyState::for_tests(clock.clone())))
+    }
+
+    async fn presence(state: &Arc<RwLock<MyState>>, player_id: i32) -> Presence {
+        player_presence(&*state.read().await, player_id)
+    }
+
+    #[tokio::test]
+    async fn players_stay_online_while_any_socket_is_open() {
+        let clock = Arc::new(FakeClock::new(NaiveDateTime::default()));
+        let state = state(&clock);
+        let (first, second) = (Sid::new(), Sid::new());
+        assert_eq!(presence(&state, 7).await, Presence::Offline);
+        player_connected(state.clone(), first, 1, 7).await;
+        player_connected(state.clone(), second, 1, 7).await;
+        // Joining again on the same socket does not count it twice
+        player_connected(state.clone(), second, 1, 7).await;
+        assert_eq!(state.read().await.connected_players[&7], 2);
+        assert_eq!(socket_player(&state, first).await, Some(7));
+
+        player_disconnected(state.clone(), first).await;
+        assert_eq!(presence(&state, 7).await, Presence::Online);
+        assert_eq!(socket_player(&state, first).await, None);
+        player_disconnected(state.clone(), second).await;
+        assert_eq!(presence(&state, 7).await, Presence::Away);
+    }
+
+    #[tokio::test]
+    async fn away_players_go_offline_after_the_grace_period() {
+        let clock = Arc::new(FakeClock::new(NaiveDateTime::default()));
+        let state = state(&clock);
+        let sid = Sid::new();
+        player_connected(state.clone(), sid, 1, 7).await;
+        player_disconnected(state.clone(), sid).await;
+        settle().await;
+
+        clock.advance(chrono::Duration::seconds(PRESENCE_GRACE_SECONDS - 1));
+        settle().await;
+        assert_eq!(presence(&state, 7).await, Presence::Away);
+        clock.advance(chrono::Duration::seconds(1));
+        settle().await;
+        assert_eq!(presence(&state, 7).await, Presence::Offline);
+    }
+
+    #[tokio::test]
+    async fn coming_back_within_the_grace_period_keeps_players_online() {
+        let clock = Arc::new(FakeClock::new(NaiveDateTime::default()));
+        let state = state(&clock);
+        let sid = Sid::new();
+        player_connected(state.clone(), sid, 1, 7).await;
+        player_disconnected(state.clone(), sid).await;
+        settle().await;
+
+        clock.advance(chrono::Duration::seconds(10));
+        player_connected(state.clone(), Sid::new(), 1, 7).await;
+        assert_eq!(presence(&state, 7).await, Presence::Online);
+        clock.advance(chrono::Duration::seconds(PRESENCE_GRACE_SECONDS));
+        settle().await;
+        assert_eq!(presence(&state, 7).await, Presence::Online);
+    }
+
+    #[tokio::test]
+    async fn only_the_latest_disconnect_starts_the_countdown() {
+        let clock = Arc::new(FakeClock::new(NaiveDateTime::default()));
+        let state = state(&clock);
+        let (first, second) = (Sid::new(), Sid::new());
+        player_connected(state.clone(), first, 1, 7).await;
+        player_disconnected(state.clone(), first).await;
+        settle().await;
+
+        // Back and gone again 20 seconds later, the first timer must not count
+        clock.advance(chrono::Duration::seconds(20));
+        player_connected(state.clone(), second, 1, 7).await;
+        player_disconnected(state.clone(), second).await;
+        settle().await;
+        clock.advance(chrono::Duration::seconds(PRESENCE_GRACE_SECONDS - 20));
+        settle().await;
+        assert_eq!(presence(&state, 7).await, Presence::Away);
+        clock.advance(chrono::Duration::seconds(20));
+        settle().await;
+        assert_eq!(presence(&state, 7).await, Presence::Offline);
+    }
+
+    #[tokio::test]
+    async fn going_offline_drops_rate_limits() {
+        let clock = Arc::new(FakeClock::new(NaiveDateTime::default()));
+        let state = state(&clock);
+        let sid = Sid::new();
+        player_connected(state.clone(), sid, 1, 7).await;
+        {
+            let mut state = state.write().await;
+            let now = state.clock.now();
+            state.chat_rate_limits.insert(7, vec![now]);
+            state.reaction_rate_limits.insert(7, vec![now]);
+        }
+        player_disconnected(state.clone(), sid).await;
+        settle().await;
+        assert!(state.read().await.chat_rate_limits.contains_key(&7));
+
+        clock.advance(chrono::Duration::seconds(PRESENCE_GRACE_SECONDS));
+        settle().await;
+        let state = state.read().await;
+        assert!(state.chat_rate_limits.is_empty());
+        assert!(state.reaction_rate_limits.is_empty());
+    }
+}
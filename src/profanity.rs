@@ -0,0 +1,128 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{normalize::word_stems, types::Language};
+
+pub const DEFAULT_BLOCKLISTS_DIR: &str = "blocklists";
+
+/// Loads every `<language>.txt` file in `dir`, one blocked word per line.
+pub fn load_blocklists(dir: &Path) -> Result<HashMap<String, Vec<String>>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut blocklists = HashMap::new();
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("txt") {
+            continue;
+        }
+        let Some(language) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let contents =
+            fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let words = contents
+            .lines()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|line| !line.is_empty())
+            .collect();
+        blocklists.insert(language.to_string(), words);
+    }
+    Ok(blocklists)
+}
+
+/// Checks the words of `text` against the blocklist of its language. Whole
+/// words are compared by their stems, so inflected forms are caught, but a
+/// blocked word hidden in a longer one ("cipa" in "principal") is not.
+pub fn contains_profanity(
+    text: &str,
+    language: &Language,
+    blocklists: &HashMap<String, Vec<String>>,
+) -> bool {
+    let Some(blocked) = blocklists.get(&language.to_string()) else {
+        return false;
+    };
+    let blocked_stems = blocked
+        .iter()
+        .map(|word| word_stems(word, language))
+        .collect::<Vec<_>>();
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .any(|word| {
+            let stems = word_stems(word, language);
+            blocked_stems
+                .iter()
+                .any(|blocked| !blocked.is_disjoint(&stems))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word_packs::{load_word_packs, DEFAULT_WORD_PACKS_DIR};
+
+    fn blocklists() -> HashMap<String, Vec<String>> {
+        HashMap::from([(
+            "pl".to_string(),
+            vec!["cipa".to_string(), "kurwa".to_string(), "chuj".to_string()],
+        )])
+    }
+
+    #[test]
+    fn catches_blocked_words_and_their_inflections() {
+        let blocklists = blocklists();
+        for text in ["cipa", "Kurwa", "kurwy", "chuja", "no i chuj"] {
+            assert!(
+                contains_profanity(text, &Language::Polish, &blocklists),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn allows_words_that_only_contain_a_blocked_one() {
+        let blocklists = blocklists();
+        for text in ["principal", "municipal", "anticipate", "participant"] {
+            assert!(
+                !contains_profanity(text, &Language::English, &blocklists),
+                "{text}"
+            );
+        }
+        for text in ["partycypacja", "emancypacja", "kurtka", "chudy"] {
+            assert!(
+                !contains_profanity(text, &Language::Polish, &blocklists),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_checks_the_list_of_the_text_language() {
+        let blocklists = blocklists();
+        assert!(!contains_profanity("cipa", &Language::German, &blocklists));
+    }
+
+    #[test]
+    fn every_language_has_a_bundled_blocklist() {
+        let blocklists = load_blocklists(Path::new(DEFAULT_BLOCKLISTS_DIR)).unwrap();
+        for (language, swear) in [
+            (Language::Polish, "kurwa"),
+            (Language::English, "Fucking"),
+            (Language::German, "Scheiße"),
+        ] {
+            assert!(contains_profanity(swear, &language, &blocklists), "{swear}");
+        }
+    }
+
+    #[test]
+    fn bundled_packs_pass_the_bundled_blocklists() {
+        let blocklists = load_blocklists(Path::new(DEFAULT_BLOCKLISTS_DIR)).unwrap();
+        let packs = load_word_packs(Path::new(DEFAULT_WORD_PACKS_DIR), 0).unwrap();
+        for pack in &packs {
+            for word in &pack.words {
+                assert!(
+                    !contains_profanity(word, &pack.language, &blocklists),
+                    "{} blocks {word}",
+                    pack.name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::{error::Error, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{ChatMessage, Player, Team},
+    my_state::MyState,
+    types::ChatChannel,
+};
+
+pub async fn create_chat_message(
+    state: Arc<RwLock<MyState>>,
+    player: &Player,
+    channel: &ChatChannel,
+    text: &str,
+) -> Result<ChatMessage, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let message = sqlx::query_as!(
+        ChatMessage,
+        "INSERT INTO chat_messages (room_id, player_id, username, channel, team, text)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *",
+        player.room_id,
+        player.id,
+        player.username,
+        channel.to_string(),
+        player.team.to_string(),
+        text
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(message)
+}
+
+/// The room's latest `limit` messages a player of `team` may read, oldest
+/// first. Without a team only the room channel is read.
+pub async fn get_chat_history(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+    team: Option<&Team>,
+    limit: i64,
+) -> Result<Vec<ChatMessage>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let mut messages = sqlx::query_as!(
+        ChatMessage,
+        "SELECT * FROM chat_messages
+        WHERE room_id = $1 AND (channel = 'all' OR team = $2)
+        ORDER BY id DESC
+        LIMIT $3",
+        room_id,
+        team.map(|team| team.to_string()),
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+    messages.reverse();
+    Ok(messages)
+}
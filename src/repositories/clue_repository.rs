@@ -0,0 +1,50 @@
+use std::{error::Error, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{Clue, Team},
+    my_state::MyState,
+};
+
+pub async fn create_clue(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    player_id: i32,
+    team: &Team,
+    word: String,
+    number: i32,
+    unlimited: bool,
+) -> Result<Clue, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let clue = sqlx::query_as!(
+        Clue,
+        "INSERT INTO clues (game_id, player_id, team, word, number, unlimited)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *",
+        game_id,
+        player_id,
+        team.to_string(),
+        word,
+        number,
+        unlimited
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(clue)
+}
+
+pub async fn get_clues_for_game(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<Vec<Clue>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let clues = sqlx::query_as!(
+        Clue,
+        "SELECT * FROM clues WHERE game_id = $1 ORDER BY id",
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(clues)
+}
@@ -0,0 +1,40 @@
+use std::{error::Error, sync::Arc};
+
+use sqlx::{Connection, PgConnection};
+use tokio::sync::RwLock;
+
+use crate::my_state::MyState;
+
+pub async fn get_custom_words_for_room(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let words = sqlx::query_scalar!(
+        "SELECT text FROM room_custom_words WHERE room_id = $1 ORDER BY id",
+        room_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(words)
+}
+
+pub async fn replace_custom_words_for_room(
+    conn: &mut PgConnection,
+    room_id: i32,
+    words: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let mut transaction = conn.begin().await?;
+    sqlx::query!("DELETE FROM room_custom_words WHERE room_id = $1", room_id)
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query!(
+        "INSERT INTO room_custom_words (room_id, text) SELECT $1, UNNEST($2::VARCHAR[])",
+        room_id,
+        words
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(())
+}
@@ -1,26 +1,33 @@
 use std::{error::Error, sync::Arc};
 
+use sqlx::PgConnection;
 use tokio::sync::RwLock;
 
-use crate::{models::Field, my_state::MyState};
+use crate::{
+    board::GeneratedField,
+    models::{Field, Team},
+    my_state::MyState,
+};
 
-pub async fn get_all_fields(state: Arc<RwLock<MyState>>) -> Result<Vec<Field>, Box<dyn Error>> {
-    let pool = &state.read().await.pool;
-    let fields = sqlx::query_as!(Field, "SELECT * FROM fields")
-        .fetch_all(pool)
-        .await?;
-    Ok(fields)
-}
-
-pub async fn get_fields_for_room_id(
+/// Words from the room's last `games` boards, lowercased.
+pub async fn get_recent_words_for_room(
     state: Arc<RwLock<MyState>>,
     room_id: i32,
-) -> Result<Vec<Field>, Box<dyn Error>> {
+    games: i64,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    if games <= 0 {
+        return Ok(Vec::new());
+    }
     let pool = &state.read().await.pool;
-    let fields = sqlx::query_as!(Field, "SELECT * FROM fields WHERE room_id = $1", room_id)
-        .fetch_all(pool)
-        .await?;
-    Ok(fields)
+    let words = sqlx::query_scalar!(
+        r#"SELECT LOWER(text) as "text!" FROM fields
+        WHERE game_id IN (SELECT id FROM games WHERE room_id = $1 ORDER BY id DESC LIMIT $2)"#,
+        room_id,
+        games
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(words)
 }
 
 pub async fn get_field_by_id(
@@ -34,13 +41,94 @@ pub async fn get_field_by_id(
     Ok(field)
 }
 
-pub async fn mark_field_as_used(state: Arc<RwLock<MyState>>, field_id: i32) -> Result<(), Box<dyn Error>> {
+/// Turns the card up unless it already is. Returns whether this call did it,
+/// so two guesses racing for the same card only play it out once.
+pub async fn mark_field_as_used(
+    state: Arc<RwLock<MyState>>,
+    field_id: i32,
+) -> Result<bool, Box<dyn Error>> {
     let pool = &state.read().await.pool;
-    sqlx::query!(
-        "UPDATE fields SET is_used = true WHERE id = $1",
+    let row = sqlx::query!(
+        "UPDATE fields SET is_used = true WHERE id = $1 AND NOT is_used RETURNING id",
         field_id
     )
-    .execute(pool)
+    .fetch_optional(pool)
     .await?;
+    Ok(row.is_some())
+}
+
+/// Records that `side`'s clue turned the card up as a bystander on a Duet board.
+/// Returns false if the card was already turned up by a racing guess.
+pub async fn mark_field_as_neutral_for(
+    state: Arc<RwLock<MyState>>,
+    field_id: i32,
+    side: &Team,
+) -> Result<bool, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let row = sqlx::query!(
+        "UPDATE fields SET neutral_for = $1
+        WHERE id = $2 AND neutral_for IS NULL AND NOT is_used
+        RETURNING id",
+        side.to_string(),
+        field_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some())
+}
+
+pub async fn get_fields_for_game_id(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<Vec<Field>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let fields = sqlx::query_as!(
+        Field,
+        "SELECT * FROM fields WHERE game_id = $1 ORDER BY position",
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(fields)
+}
+
+pub async fn create_fields_for_game(
+    conn: &mut PgConnection,
+    game_id: i32,
+    fields: Vec<GeneratedField>,
+) -> Result<(), Box<dyn Error>> {
+    let mut query = String::from(
+        "INSERT INTO fields (game_id, text, team, team_b, position, row_index, col_index) VALUES ",
+    );
+    for i in 0..fields.len() {
+        if i > 0 {
+            query.push_str(", ");
+        }
+        let p = i * 7;
+        query.push_str(&format!(
+            "(${}, ${}, ${}, ${}, ${}, ${}, ${})",
+            p + 1,
+            p + 2,
+            p + 3,
+            p + 4,
+            p + 5,
+            p + 6,
+            p + 7
+        ));
+    }
+    query.push_str(" ON CONFLICT DO NOTHING");
+
+    let mut query_builder = sqlx::query(&query);
+    for field in fields {
+        query_builder = query_builder
+            .bind(game_id)
+            .bind(field.text)
+            .bind(field.team.to_string())
+            .bind(field.team_b.map(|team| team.to_string()))
+            .bind(field.position)
+            .bind(field.row_index)
+            .bind(field.col_index);
+    }
+    query_builder.execute(&mut *conn).await?;
     Ok(())
 }
@@ -0,0 +1,239 @@
+use std::{error::Error, sync::Arc};
+
+use chrono::NaiveDateTime;
+use sqlx::PgConnection;
+use tokio::sync::RwLock;
+
+use crate::{
+    board::BoardSize,
+    models::{Game, Team},
+    my_state::MyState,
+    types::{GameStage, TurnPhase},
+};
+
+/// Stores a new game for `teams`, given in turn order.
+pub async fn create_game(
+    conn: &mut PgConnection,
+    room_id: i32,
+    teams: &[Team],
+    size: &BoardSize,
+    seed: i64,
+    turns: Option<i32>,
+) -> Result<Game, Box<dyn Error>> {
+    let game = sqlx::query_as!(
+        Game,
+        "INSERT INTO games
+            (room_id, starting_team, current_team, board_rows, board_cols, seed, turns_remaining, teams)
+        VALUES ($1, $2, $2, $3, $4, $5, $6, $7)
+        RETURNING *",
+        room_id,
+        teams[0].to_string(),
+        size.rows,
+        size.cols,
+        seed,
+        turns,
+        &teams.iter().map(|team| team.to_string()).collect::<Vec<_>>()
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(game)
+}
+
+pub async fn get_game_by_id(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<Option<Game>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let game = sqlx::query_as!(Game, "SELECT * FROM games WHERE id = $1", game_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(game)
+}
+
+pub async fn get_current_game_for_room(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<Option<Game>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let game = sqlx::query_as!(
+        Game,
+        "SELECT * FROM games WHERE room_id = $1 ORDER BY id DESC LIMIT 1",
+        room_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(game)
+}
+
+pub async fn get_finished_games_for_room(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<Vec<Game>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let games = sqlx::query_as!(
+        Game,
+        "SELECT * FROM games WHERE room_id = $1 AND game_stage = 'finished' ORDER BY id",
+        room_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(games)
+}
+
+pub async fn delete_game(conn: &mut PgConnection, game_id: i32) -> Result<(), Box<dyn Error>> {
+    sqlx::query!("DELETE FROM games WHERE id = $1", game_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Moves the game to its next stage. `now` becomes the start time when the
+/// game gets under way, taken from the app's clock like turn deadlines.
+pub async fn advance_game_stage(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    now: NaiveDateTime,
+) -> Result<(), Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let Some(Game { game_stage, .. }) = get_game_by_id(state.clone(), game_id).await? else {
+        return Err("Game not found".into());
+    };
+    sqlx::query!(
+        "UPDATE games
+        SET game_stage = $1,
+            started_at = CASE WHEN $1::VARCHAR = 'in_progress' THEN $2 ELSE started_at END
+        WHERE id = $3",
+        game_stage.next().to_string(),
+        now,
+        game_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Hands the turn to `next_team`, which starts by waiting for a clue, and
+/// uses up one of the game's turns if they are limited.
+pub async fn change_game_current_team(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    next_team: &Team,
+) -> Result<Game, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let game = sqlx::query_as!(
+        Game,
+        "UPDATE games
+        SET current_team = $1, turn_phase = $2, guesses_remaining = NULL, turn_deadline = NULL,
+            turns_remaining = turns_remaining - 1
+        WHERE id = $3
+        RETURNING *",
+        next_team.to_string(),
+        TurnPhase::Clue.to_string(),
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(game)
+}
+
+/// Lets the current team guess after its clue. `guesses` is `None` for no limit.
+pub async fn start_guessing(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    guesses: Option<i32>,
+) -> Result<Game, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let game = sqlx::query_as!(
+        Game,
+        "UPDATE games SET turn_phase = $1, guesses_remaining = $2 WHERE id = $3 RETURNING *",
+        TurnPhase::Guess.to_string(),
+        guesses,
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(game)
+}
+
+pub async fn set_turn_deadline(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    deadline: Option<NaiveDateTime>,
+) -> Result<Game, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let game = sqlx::query_as!(
+        Game,
+        "UPDATE games SET turn_deadline = $1 WHERE id = $2 RETURNING *",
+        deadline,
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(game)
+}
+
+pub async fn get_games_with_turn_deadline(
+    state: Arc<RwLock<MyState>>,
+) -> Result<Vec<Game>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let games = sqlx::query_as!(
+        Game,
+        "SELECT * FROM games WHERE turn_deadline IS NOT NULL AND game_stage != 'finished'"
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(games)
+}
+
+pub async fn use_guess(state: Arc<RwLock<MyState>>, game_id: i32) -> Result<Game, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let game = sqlx::query_as!(
+        Game,
+        "UPDATE games SET guesses_remaining = guesses_remaining - 1 WHERE id = $1 RETURNING *",
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(game)
+}
+
+pub async fn add_eliminated_team(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    team: &Team,
+) -> Result<Game, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let game = sqlx::query_as!(
+        Game,
+        "UPDATE games SET eliminated_teams = array_append(eliminated_teams, $1) WHERE id = $2 RETURNING *",
+        team.to_string(),
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(game)
+}
+
+pub async fn finish_game(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    winner: Option<&Team>,
+    points: i32,
+) -> Result<Game, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let game = sqlx::query_as!(
+        Game,
+        "UPDATE games
+        SET game_stage = $1, winner = $2, points = $3, finished_at = CURRENT_TIMESTAMP,
+            turn_deadline = NULL
+        WHERE id = $4
+        RETURNING *",
+        GameStage::Finished.to_string(),
+        winner.map(|team| team.to_string()),
+        points,
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(game)
+}
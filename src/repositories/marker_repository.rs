@@ -0,0 +1,87 @@
+use std::{error::Error, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{FieldMarker, Team},
+    my_state::MyState,
+    types::MarkerKind,
+};
+
+/// Marks a card for the player's team, replacing any mark they had on it.
+pub async fn set_field_marker(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    field_id: i32,
+    player_id: i32,
+    team: &Team,
+    kind: &MarkerKind,
+) -> Result<FieldMarker, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let marker = sqlx::query_as!(
+        FieldMarker,
+        "INSERT INTO field_markers (game_id, field_id, player_id, team, kind)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (field_id, player_id)
+        DO UPDATE SET kind = $5, created_at = CURRENT_TIMESTAMP
+        RETURNING *",
+        game_id,
+        field_id,
+        player_id,
+        team.to_string(),
+        kind.to_string()
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(marker)
+}
+
+pub async fn delete_field_marker(
+    state: Arc<RwLock<MyState>>,
+    field_id: i32,
+    player_id: i32,
+) -> Result<(), Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    sqlx::query!(
+        "DELETE FROM field_markers WHERE field_id = $1 AND player_id = $2",
+        field_id,
+        player_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The marks `team` put on the game's cards, which only its players may see.
+pub async fn get_field_markers_for_team(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    team: &Team,
+) -> Result<Vec<FieldMarker>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let markers = sqlx::query_as!(
+        FieldMarker,
+        "SELECT * FROM field_markers WHERE game_id = $1 AND team = $2 ORDER BY id",
+        game_id,
+        team.to_string()
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(markers)
+}
+
+/// Removes every mark of the game, returning the teams that had any.
+pub async fn delete_field_markers_for_game(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<Vec<Team>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let teams = sqlx::query_scalar!(
+        r#"WITH deleted AS (DELETE FROM field_markers WHERE game_id = $1 RETURNING team)
+        SELECT DISTINCT team as "team!" FROM deleted"#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(teams.into_iter().map(Team::from).collect())
+}
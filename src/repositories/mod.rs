@@ -1,3 +1,13 @@
+pub mod chat_repository;
+pub mod clue_repository;
+pub mod custom_word_repository;
 pub mod field_repository;
+pub mod game_repository;
+pub mod marker_repository;
 pub mod player_repository;
+pub mod reaction_repository;
 pub mod room_repository;
+pub mod score_repository;
+pub mod time_bank_repository;
+pub mod vote_repository;
+pub mod word_pack_repository;
@@ -3,21 +3,61 @@ use std::{error::Error, sync::Arc};
 use tokio::sync::RwLock;
 
 use crate::{
-    models::{Field, Player},
+    models::{Field, Player, Team},
     my_state::MyState,
+    types::Role,
 };
 
+/// Everyone playing in the room, spectators are left out.
 pub async fn get_players_by_room_id(
     state: Arc<RwLock<MyState>>,
     room_id: i32,
 ) -> Result<Vec<Player>, Box<dyn Error>> {
     let pool = &state.read().await.pool;
-    let teams = sqlx::query_as!(Player, "SELECT * FROM players WHERE room_id = $1", room_id)
-        .fetch_all(pool)
-        .await?;
+    let teams = sqlx::query_as!(
+        Player,
+        "SELECT * FROM players WHERE room_id = $1 AND role != $2",
+        room_id,
+        Role::Spectator.to_string()
+    )
+    .fetch_all(pool)
+    .await?;
     Ok(teams)
 }
 
+pub async fn get_spectators_by_room_id(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<Vec<Player>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let spectators = sqlx::query_as!(
+        Player,
+        "SELECT * FROM players WHERE room_id = $1 AND role = $2 ORDER BY id",
+        room_id,
+        Role::Spectator.to_string()
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(spectators)
+}
+
+/// The host is whoever joined the room first to play.
+pub async fn get_room_host(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<Option<Player>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let host = sqlx::query_as!(
+        Player,
+        "SELECT * FROM players WHERE room_id = $1 AND role != $2 ORDER BY id LIMIT 1",
+        room_id,
+        Role::Spectator.to_string()
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(host)
+}
+
 pub async fn is_player_id_in_room(
     state: Arc<RwLock<MyState>>,
     player_id: i32,
@@ -61,3 +101,81 @@ pub async fn create_player_for_the_room_id(
     .await?;
     Ok(player)
 }
+
+/// Adds someone who only watches the room's games. Spectators have no team
+/// and can join at any point of a game.
+pub async fn create_spectator_for_the_room_id(
+    state: Arc<RwLock<MyState>>,
+    username: String,
+    room_id: i32,
+) -> Result<Player, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let spectator = sqlx::query_as!(
+        Player,
+        "INSERT INTO players (room_id, username, team, role) VALUES ($1, $2, $3, $4) RETURNING *",
+        room_id,
+        username,
+        Team::Neutral.to_string(),
+        Role::Spectator.to_string()
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(spectator)
+}
+
+/// Moves every player to the next of `teams`, which swaps two teams around.
+pub async fn swap_player_teams_in_room(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+    teams: &[Team],
+) -> Result<(), Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    sqlx::query!(
+        "UPDATE players
+        SET team = ($2::VARCHAR[])[array_position($2::VARCHAR[], team) % cardinality($2::VARCHAR[]) + 1]
+        WHERE room_id = $1 AND team = ANY($2::VARCHAR[])",
+        room_id,
+        &teams.iter().map(|team| team.to_string()).collect::<Vec<_>>()
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn set_player_role(
+    state: Arc<RwLock<MyState>>,
+    player_id: i32,
+    role: &Role,
+) -> Result<(), Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    sqlx::query!(
+        "UPDATE players SET role = $1 WHERE id = $2",
+        role.to_string(),
+        player_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Hands the shower role to the next player of each team, in join order.
+pub async fn rotate_showers_in_room(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<(), Box<dyn Error>> {
+    let players = get_players_by_room_id(state.clone(), room_id).await?;
+    for team in [Team::Red, Team::Blue, Team::Yellow] {
+        let mut members = players.iter().filter(|p| p.team == team).collect::<Vec<_>>();
+        if members.len() < 2 {
+            continue;
+        }
+        members.sort_by_key(|p| p.id);
+        let Some(current) = members.iter().position(|p| p.role == Role::Shower) else {
+            continue;
+        };
+        let next = (current + 1) % members.len();
+        set_player_role(state.clone(), members[current].id, &Role::Guesser).await?;
+        set_player_role(state.clone(), members[next].id, &Role::Shower).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::{error::Error, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{Reaction, ReactionCount},
+    my_state::MyState,
+    types::ReactionKind,
+};
+
+pub async fn create_reaction(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+    game_id: Option<i32>,
+    player_id: i32,
+    reaction: &ReactionKind,
+    field_id: Option<i32>,
+    target_player_id: Option<i32>,
+) -> Result<Reaction, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let reaction = sqlx::query_as!(
+        Reaction,
+        "INSERT INTO reactions (room_id, game_id, player_id, reaction, field_id, target_player_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *",
+        room_id,
+        game_id,
+        player_id,
+        reaction.to_string(),
+        field_id,
+        target_player_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(reaction)
+}
+
+/// How often each reaction was sent during the game, most frequent first.
+pub async fn get_reaction_counts_for_game(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<Vec<ReactionCount>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let counts = sqlx::query_as!(
+        ReactionCount,
+        r#"SELECT reaction, COUNT(*) as "count!"
+        FROM reactions
+        WHERE game_id = $1
+        GROUP BY reaction
+        ORDER BY COUNT(*) DESC, reaction"#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(counts)
+}
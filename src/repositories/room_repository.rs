@@ -1,10 +1,18 @@
 use std::{error::Error, sync::Arc};
 
+use sqlx::PgConnection;
 use tokio::sync::RwLock;
 
-use crate::{models::{Room, Team}, my_state::MyState, types::GameStage};
+use crate::{
+    models::Room,
+    my_state::MyState,
+    types::{Language, RoomSettings},
+};
 
-pub async fn get_room_by_id(state: Arc<RwLock<MyState>>, room_id: i32) -> Result<Room, Box<dyn Error>> {
+pub async fn get_room_by_id(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<Room, Box<dyn Error>> {
     let pool = &state.read().await.pool;
     let room = sqlx::query_as!(Room, "SELECT * FROM rooms WHERE id = $1", room_id)
         .fetch_one(pool)
@@ -12,33 +20,96 @@ pub async fn get_room_by_id(state: Arc<RwLock<MyState>>, room_id: i32) -> Result
     Ok(room)
 }
 
-pub async fn advance_room_game_stage(state: Arc<RwLock<MyState>>, room_id: i32) -> Result<(), Box<dyn Error>> {
+pub async fn get_rooms(
+    state: Arc<RwLock<MyState>>,
+    language: Option<&Language>,
+) -> Result<Vec<Room>, Box<dyn Error>> {
     let pool = &state.read().await.pool;
-    let Room {  game_stage ,..} = get_room_by_id(state.clone(), room_id).await?;
-    sqlx::query!(
-        "UPDATE rooms SET game_stage = $1 WHERE id = $2",
-        game_stage.next().to_string(),
-        room_id
+    let rooms = sqlx::query_as!(
+        Room,
+        "SELECT * FROM rooms WHERE $1::VARCHAR IS NULL OR language = $1 ORDER BY id",
+        language.map(|language| language.to_string())
     )
-    .execute(pool)
+    .fetch_all(pool)
     .await?;
-    Ok(())
+    Ok(rooms)
 }
 
-pub async fn change_room_current_team(state: Arc<RwLock<MyState>>, room_id: i32) -> Result<(), Box<dyn Error>> {
-    let pool = &state.read().await.pool;
-    let Room {  current_team ,..} = get_room_by_id(state.clone(), room_id).await?;
-    let next_team = match current_team {
-        Team::Red => Team::Blue,
-        Team::Blue => Team::Red,
-        _ => Team::Red,
-    };
-    sqlx::query!(
-        "UPDATE rooms SET current_team = $1 WHERE id = $2",
-        next_team.to_string(),
+/// Takes a connection rather than the state, so the room and its first game
+/// can be created in one transaction.
+pub async fn create_room(conn: &mut PgConnection) -> Result<Room, Box<dyn Error>> {
+    let room = sqlx::query_as!(Room, "INSERT INTO rooms DEFAULT VALUES RETURNING *")
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(room)
+}
+
+/// Applies every setting that is present, leaving the others untouched.
+pub async fn update_room_settings(
+    conn: &mut PgConnection,
+    room_id: i32,
+    settings: &RoomSettings,
+) -> Result<Room, Box<dyn Error>> {
+    let room = sqlx::query_as!(
+        Room,
+        "UPDATE rooms
+        SET board_rows = COALESCE($1, board_rows),
+            board_cols = COALESCE($2, board_cols),
+            scoring_mode = COALESCE($3, scoring_mode),
+            word_pack = COALESCE($4, word_pack),
+            custom_word_ratio = COALESCE($5, custom_word_ratio),
+            language = COALESCE($6, language),
+            avoid_recent_games = COALESCE($7, avoid_recent_games),
+            difficulty = COALESCE($8, difficulty),
+            clue_strictness = COALESCE($9, clue_strictness),
+            zero_clue_rule = COALESCE($10, zero_clue_rule),
+            allow_unlimited_clues = COALESCE($11, allow_unlimited_clues),
+            turn_seconds = COALESCE($12, turn_seconds),
+            time_bank_seconds = COALESCE($13, time_bank_seconds),
+            game_mode = COALESCE($14, game_mode),
+            team_count = COALESCE($15, team_count),
+            spectators_see_key = COALESCE($16, spectators_see_key),
+            spectator_key_delay_seconds = COALESCE($17, spectator_key_delay_seconds),
+            reveal_mode = COALESCE($18, reveal_mode),
+            consensus_percent = COALESCE($19, consensus_percent),
+            mute_showers_while_guessing = COALESCE($20, mute_showers_while_guessing)
+        WHERE id = $21
+        RETURNING *",
+        settings.board_rows,
+        settings.board_cols,
+        settings.scoring_mode.as_ref().map(|mode| mode.to_string()),
+        settings.word_pack,
+        settings.custom_word_ratio,
+        settings
+            .language
+            .as_ref()
+            .map(|language| language.to_string()),
+        settings.avoid_recent_games,
+        settings
+            .difficulty
+            .as_ref()
+            .map(|difficulty| difficulty.to_string()),
+        settings
+            .clue_strictness
+            .as_ref()
+            .map(|strictness| strictness.to_string()),
+        settings
+            .zero_clue_rule
+            .as_ref()
+            .map(|rule| rule.to_string()),
+        settings.allow_unlimited_clues,
+        settings.turn_seconds,
+        settings.time_bank_seconds,
+        settings.game_mode.as_ref().map(|mode| mode.to_string()),
+        settings.team_count,
+        settings.spectators_see_key,
+        settings.spectator_key_delay_seconds,
+        settings.reveal_mode.as_ref().map(|mode| mode.to_string()),
+        settings.consensus_percent,
+        settings.mute_showers_while_guessing,
         room_id
     )
-    .execute(pool)
+    .fetch_one(&mut *conn)
     .await?;
-    Ok(())
+    Ok(room)
 }
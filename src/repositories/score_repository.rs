@@ -0,0 +1,77 @@
+use std::{error::Error, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{PlayerScore, Team, TeamScore},
+    my_state::MyState,
+};
+
+/// Stores the outcome of a finished game for every player on a team. A Duet
+/// game is won by everyone together, which `Team::Green` stands for.
+pub async fn record_player_results(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    room_id: i32,
+    winner: &Team,
+    points: i32,
+) -> Result<(), Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    sqlx::query!(
+        "INSERT INTO player_results (game_id, player_id, team, won, points)
+        SELECT $1, id, team, team = $2 OR $2 = 'green',
+            CASE WHEN team = $2 OR $2 = 'green' THEN $3 ELSE 0 END
+        FROM players
+        WHERE room_id = $4 AND team IN ('red', 'blue', 'yellow')",
+        game_id,
+        winner.to_string(),
+        points,
+        room_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_team_scores_for_room(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<Vec<TeamScore>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let scores = sqlx::query_as!(
+        TeamScore,
+        r#"SELECT winner as "team!", COUNT(*) as "wins!", SUM(points) as "points!"
+        FROM games
+        WHERE room_id = $1 AND winner IS NOT NULL
+        GROUP BY winner
+        ORDER BY winner"#,
+        room_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(scores)
+}
+
+pub async fn get_player_scores_for_room(
+    state: Arc<RwLock<MyState>>,
+    room_id: i32,
+) -> Result<Vec<PlayerScore>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let scores = sqlx::query_as!(
+        PlayerScore,
+        r#"SELECT
+            players.id as player_id,
+            players.username,
+            COUNT(*) FILTER (WHERE player_results.won) as "wins!",
+            SUM(player_results.points) as "points!"
+        FROM player_results
+        JOIN players ON players.id = player_results.player_id
+        WHERE players.room_id = $1
+        GROUP BY players.id, players.username
+        ORDER BY 4 DESC, 3 DESC"#,
+        room_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(scores)
+}
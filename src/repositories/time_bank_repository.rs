@@ -0,0 +1,84 @@
+use std::{error::Error, sync::Arc};
+
+use sqlx::PgConnection;
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{Team, TimeBank},
+    my_state::MyState,
+};
+
+pub async fn create_time_banks(
+    conn: &mut PgConnection,
+    game_id: i32,
+    teams: &[Team],
+    remaining_ms: i64,
+) -> Result<Vec<TimeBank>, Box<dyn Error>> {
+    let teams = teams
+        .iter()
+        .map(|team| team.to_string())
+        .collect::<Vec<_>>();
+    let time_banks = sqlx::query_as!(
+        TimeBank,
+        "INSERT INTO time_banks (game_id, team, remaining_ms)
+        SELECT $1, UNNEST($2::VARCHAR[]), $3
+        RETURNING *",
+        game_id,
+        &teams,
+        remaining_ms
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(time_banks)
+}
+
+pub async fn get_time_banks_for_game(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<Vec<TimeBank>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let time_banks = sqlx::query_as!(
+        TimeBank,
+        "SELECT * FROM time_banks WHERE game_id = $1 ORDER BY id",
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(time_banks)
+}
+
+pub async fn get_time_bank(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    team: &Team,
+) -> Result<Option<TimeBank>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let time_bank = sqlx::query_as!(
+        TimeBank,
+        "SELECT * FROM time_banks WHERE game_id = $1 AND team = $2",
+        game_id,
+        team.to_string()
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(time_bank)
+}
+
+pub async fn set_time_bank(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    team: &Team,
+    remaining_ms: i64,
+) -> Result<(), Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    sqlx::query!(
+        "UPDATE time_banks SET remaining_ms = $1, updated_at = CURRENT_TIMESTAMP
+        WHERE game_id = $2 AND team = $3",
+        remaining_ms,
+        game_id,
+        team.to_string()
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
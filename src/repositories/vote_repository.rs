@@ -0,0 +1,74 @@
+use std::{error::Error, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use crate::{models::FieldVote, my_state::MyState};
+
+/// Stores the player's vote for a card, replacing any vote they had. Returns
+/// `None` when the card has been turned up in the meantime.
+pub async fn set_field_vote(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    field_id: i32,
+    player_id: i32,
+) -> Result<Option<FieldVote>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let vote = sqlx::query_as!(
+        FieldVote,
+        "INSERT INTO field_votes (game_id, field_id, player_id)
+        SELECT $1, $2, $3
+        WHERE EXISTS (SELECT 1 FROM fields WHERE id = $2 AND NOT is_used)
+        ON CONFLICT (game_id, player_id)
+        DO UPDATE SET field_id = $2, created_at = CURRENT_TIMESTAMP
+        RETURNING *",
+        game_id,
+        field_id,
+        player_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(vote)
+}
+
+pub async fn delete_field_vote(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    player_id: i32,
+) -> Result<(), Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    sqlx::query!(
+        "DELETE FROM field_votes WHERE game_id = $1 AND player_id = $2",
+        game_id,
+        player_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_field_votes_for_game(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<Vec<FieldVote>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let votes = sqlx::query_as!(
+        FieldVote,
+        "SELECT * FROM field_votes WHERE game_id = $1 ORDER BY id",
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(votes)
+}
+
+/// Removes every vote of the game, returning how many there were.
+pub async fn delete_field_votes_for_game(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<u64, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let result = sqlx::query!("DELETE FROM field_votes WHERE game_id = $1", game_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,196 @@
+use std::{error::Error, sync::Arc};
+
+use sqlx::PgConnection;
+use tokio::sync::RwLock;
+
+use crate::{
+    models::CommunityWordPack,
+    my_state::MyState,
+    types::{Language, WordPackRequest, WordPackStatus},
+    word_packs::{WordPackSummary, COMMUNITY_WORD_PACK_PREFIX},
+};
+
+pub async fn create_community_word_pack(
+    state: Arc<RwLock<MyState>>,
+    request: &WordPackRequest,
+    words: &[String],
+    edit_token: &str,
+    forked_from: Option<i32>,
+) -> Result<CommunityWordPack, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let mut transaction = pool.begin().await?;
+    let word_pack = sqlx::query_as!(
+        CommunityWordPack,
+        "INSERT INTO word_packs (name, language, tags, author, edit_token, forked_from)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *",
+        request.name,
+        request.language.to_string(),
+        &request.tags,
+        request.author,
+        edit_token,
+        forked_from
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO word_pack_words (word_pack_id, text) SELECT $1, UNNEST($2::VARCHAR[])",
+        word_pack.id,
+        words
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(word_pack)
+}
+
+pub async fn get_community_word_pack_by_id(
+    state: Arc<RwLock<MyState>>,
+    word_pack_id: i32,
+) -> Result<Option<CommunityWordPack>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let word_pack = sqlx::query_as!(
+        CommunityWordPack,
+        "SELECT * FROM word_packs WHERE id = $1",
+        word_pack_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(word_pack)
+}
+
+pub async fn get_community_word_pack_words(
+    state: Arc<RwLock<MyState>>,
+    word_pack_id: i32,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let words = sqlx::query_scalar!(
+        "SELECT text FROM word_pack_words WHERE word_pack_id = $1 ORDER BY id",
+        word_pack_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(words)
+}
+
+pub async fn update_community_word_pack(
+    state: Arc<RwLock<MyState>>,
+    word_pack_id: i32,
+    request: &WordPackRequest,
+    words: &[String],
+    status: &WordPackStatus,
+) -> Result<CommunityWordPack, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let mut transaction = pool.begin().await?;
+    let word_pack = sqlx::query_as!(
+        CommunityWordPack,
+        "UPDATE word_packs
+        SET name = $1, language = $2, tags = $3, status = $4, updated_at = CURRENT_TIMESTAMP
+        WHERE id = $5
+        RETURNING *",
+        request.name,
+        request.language.to_string(),
+        &request.tags,
+        status.to_string(),
+        word_pack_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM word_pack_words WHERE word_pack_id = $1",
+        word_pack_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO word_pack_words (word_pack_id, text) SELECT $1, UNNEST($2::VARCHAR[])",
+        word_pack_id,
+        words
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(word_pack)
+}
+
+pub async fn set_community_word_pack_status(
+    state: Arc<RwLock<MyState>>,
+    word_pack_id: i32,
+    status: &WordPackStatus,
+) -> Result<CommunityWordPack, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let word_pack = sqlx::query_as!(
+        CommunityWordPack,
+        "UPDATE word_packs SET status = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2 RETURNING *",
+        status.to_string(),
+        word_pack_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(word_pack)
+}
+
+pub async fn delete_community_word_pack(
+    state: Arc<RwLock<MyState>>,
+    word_pack_id: i32,
+) -> Result<(), Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    sqlx::query!("DELETE FROM word_packs WHERE id = $1", word_pack_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn increment_community_word_pack_usage(
+    conn: &mut PgConnection,
+    word_pack_id: i32,
+) -> Result<(), Box<dyn Error>> {
+    sqlx::query!(
+        "UPDATE word_packs SET usage_count = usage_count + 1 WHERE id = $1",
+        word_pack_id
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Approved packs for the room picker, most used first.
+pub async fn get_approved_community_word_pack_summaries(
+    state: Arc<RwLock<MyState>>,
+    language: Option<&Language>,
+) -> Result<Vec<WordPackSummary>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let summaries = sqlx::query_as!(
+        WordPackSummary,
+        r#"SELECT
+            $1::VARCHAR || id as "key!",
+            name,
+            language,
+            tags,
+            (SELECT COUNT(*) FROM word_pack_words WHERE word_pack_id = word_packs.id) as "word_count!",
+            usage_count
+        FROM word_packs
+        WHERE status = 'approved' AND ($2::VARCHAR IS NULL OR language = $2)
+        ORDER BY usage_count DESC, name"#,
+        COMMUNITY_WORD_PACK_PREFIX,
+        language.map(|language| language.to_string())
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(summaries)
+}
+
+pub async fn get_community_word_packs_by_status(
+    state: Arc<RwLock<MyState>>,
+    status: &WordPackStatus,
+) -> Result<Vec<CommunityWordPack>, Box<dyn Error>> {
+    let pool = &state.read().await.pool;
+    let word_packs = sqlx::query_as!(
+        CommunityWordPack,
+        "SELECT * FROM word_packs WHERE status = $1 ORDER BY updated_at",
+        status.to_string()
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(word_packs)
+}
@@ -0,0 +1,705 @@
+use chrono::NaiveDateTime;
+
+use crate::{
+    models::{Field, FieldVote, Game, Player, Room, Team},
+    normalize::{fold_diacritics, word_stems},
+    types::{
+        ClueStrictness, GameMode, Language, PlayerField, PlayerWithPresence, Presence, Role,
+        ScoringMode, ZeroClueRule,
+    },
+};
+
+/// The team of `teams_in_play` that has found all its cards, if any. `fields`
+/// is the whole board with the latest card already marked as used. Assassins
+/// knock a team out instead, see `game_flow::eliminate_team`.
+pub fn winner_after_reveal(fields: &[Field], teams_in_play: &[Team]) -> Option<Team> {
+    teams_in_play
+        .iter()
+        .find(|team| {
+            fields
+                .iter()
+                .filter(|field| field.team == **team)
+                .all(|field| field.is_used)
+        })
+        .cloned()
+}
+
+/// Points the winner gets. In margin scoring that is the number of cards the
+/// losing teams still had to find, but never less than one.
+pub fn points_for_win(mode: &ScoringMode, fields: &[Field], winner: &Team) -> i32 {
+    match mode {
+        ScoringMode::Wins => 1,
+        ScoringMode::Margin => {
+            let remaining = fields
+                .iter()
+                .filter(|field| {
+                    field.team != *winner
+                        && matches!(field.team, Team::Red | Team::Blue | Team::Yellow)
+                        && !field.is_used
+                })
+                .count() as i32;
+            remaining.max(1)
+        }
+    }
+}
+
+/// Points for clearing a Duet board together. In margin scoring that is the
+/// number of turns the players had to spare, but never less than one.
+pub fn points_for_duet_win(mode: &ScoringMode, turns_remaining: Option<i32>) -> i32 {
+    match mode {
+        ScoringMode::Wins => 1,
+        ScoringMode::Margin => turns_remaining.unwrap_or(0).max(1),
+    }
+}
+
+/// Whether every card that is green on either key of a Duet board is revealed.
+pub fn duet_board_cleared(fields: &[Field]) -> bool {
+    fields
+        .iter()
+        .filter(|field| {
+            field.key_for(&Team::Red) == Team::Green || field.key_for(&Team::Blue) == Team::Green
+        })
+        .all(|field| field.is_used)
+}
+
+/// What turning up a Duet card means for the side that gave the clue.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DuetReveal {
+    Green,
+    /// An assassin on the clue giver's key, which loses the game for both.
+    Assassin,
+    /// A bystander that stays open for the other side's clues.
+    Bystander,
+    /// A bystander the other side already turned up, now out of play.
+    BystanderForBoth,
+}
+
+/// Checks a Duet card against the key of `side`, the side that gave the clue.
+pub fn duet_reveal(field: &Field, side: &Team) -> DuetReveal {
+    match field.key_for(side) {
+        Team::Green => DuetReveal::Green,
+        Team::Black => DuetReveal::Assassin,
+        _ if field.neutral_for.is_some() => DuetReveal::BystanderForBoth,
+        _ => DuetReveal::Bystander,
+    }
+}
+
+/// Who gives the next Duet clue. The sides take turns, unless every green
+/// card on the other side's key has been found already.
+pub fn next_duet_clue_giver(current: &Team, fields: &[Field]) -> Team {
+    let next = current.opposite();
+    let next_has_greens = fields
+        .iter()
+        .any(|field| !field.is_used && field.key_for(&next) == Team::Green);
+    if next_has_greens {
+        next
+    } else {
+        current.clone()
+    }
+}
+
+/// Checks a clue against the unrevealed words on the board, comparing without
+/// case and diacritics. Returns why the clue is not allowed, if it is not.
+pub fn clue_violation(
+    clue: &str,
+    fields: &[Field],
+    strictness: &ClueStrictness,
+    language: &Language,
+) -> Option<String> {
+    if *strictness == ClueStrictness::Off {
+        return None;
+    }
+    let folded_clue = fold_diacritics(clue, language);
+    let clue_stems = word_stems(clue, language);
+    fields
+        .iter()
+        .filter(|field| !field.is_used)
+        .find_map(|field| {
+            let word = fold_diacritics(&field.text, language);
+            if word == folded_clue {
+                return Some(format!("Clue cannot be the board word {}", field.text));
+            }
+            if *strictness == ClueStrictness::Exact {
+                return None;
+            }
+            if !clue_stems.is_disjoint(&word_stems(&field.text, language)) {
+                return Some(format!(
+                    "Clue shares a stem with the board word {}",
+                    field.text
+                ));
+            }
+            if *strictness == ClueStrictness::Stem {
+                return None;
+            }
+            let (shorter, longer) = if word.chars().count() < folded_clue.chars().count() {
+                (&word, &folded_clue)
+            } else {
+                (&folded_clue, &word)
+            };
+            if shorter.chars().count() >= 3 && longer.contains(shorter.as_str()) {
+                return Some(format!("Clue overlaps with the board word {}", field.text));
+            }
+            None
+        })
+}
+
+/// Guesses a clue allows: one more than its number, `None` for no limit.
+/// The number can be at most the `cards_left` the team has still to find.
+pub fn guesses_for_clue(
+    number: i32,
+    cards_left: i32,
+    unlimited: bool,
+    zero_clue_rule: &ZeroClueRule,
+    allow_unlimited_clues: bool,
+) -> Result<Option<i32>, String> {
+    if unlimited {
+        if !allow_unlimited_clues {
+            return Err("Unlimited clues are not allowed in this room".to_string());
+        }
+        return Ok(None);
+    }
+    if number > cards_left {
+        return Err(format!(
+            "Clue number cannot be more than the {cards_left} cards your team has left"
+        ));
+    }
+    match (number, zero_clue_rule) {
+        (0, ZeroClueRule::Unlimited) => Ok(None),
+        (0, ZeroClueRule::One) => Ok(Some(1)),
+        (0, ZeroClueRule::Forbidden) => Err("Clues of 0 are not allowed in this room".to_string()),
+        (number, _) => Ok(Some(number + 1)),
+    }
+}
+
+/// Whether `player` is one of the guessers of the current turn.
+pub fn guesses_this_turn(player: &Player, game: &Game, mode: &GameMode) -> bool {
+    match mode {
+        GameMode::Classic => player.role == Role::Guesser && player.team == game.current_team,
+        GameMode::Duet => player.role != Role::Spectator && player.team != game.current_team,
+    }
+}
+
+/// Votes a card needs when `voters` guessers have a say: `percent` of them,
+/// rounded up, and at least one.
+pub fn votes_needed(voters: usize, percent: i32) -> usize {
+    (voters * percent.clamp(1, 100) as usize)
+        .div_ceil(100)
+        .max(1)
+}
+
+/// Guessers who have a say in consensus mode: those who are not offline, and
+/// anyone who already voted even if they have left since.
+pub fn consensus_voters(
+    players: &[PlayerWithPresence],
+    votes: &[FieldVote],
+    game: &Game,
+    mode: &GameMode,
+) -> usize {
+    players
+        .iter()
+        .filter(|entry| guesses_this_turn(&entry.player, game, mode))
+        .filter(|entry| {
+            entry.presence != Presence::Offline
+                || votes.iter().any(|vote| vote.player_id == entry.player.id)
+        })
+        .count()
+}
+
+pub const MAX_SPECTATOR_KEY_DELAY_SECONDS: i32 = 3600;
+
+/// Whether spectators of the room see the whole key by `now`.
+pub fn spectators_see_key(room: &Room, game: &Game, now: NaiveDateTime) -> bool {
+    let delay = chrono::Duration::seconds(room.spectator_key_delay_seconds as i64);
+    room.spectators_see_key
+        && game
+            .started_at
+            .is_some_and(|started| now >= started + delay)
+}
+
+/// What `player` may see of a card, where `full_key` shows everything, as
+/// once the game is over. Classic showers see the whole key and guessers only
+/// revealed cards. Each Duet player sees their own side of the key, and green
+/// cards once anyone has found them. Spectators see what onlookers do.
+pub fn field_for_player(
+    field: &Field,
+    player: &Player,
+    mode: &GameMode,
+    full_key: bool,
+) -> PlayerField {
+    if player.role == Role::Spectator {
+        return field_for_onlooker(field, mode, full_key);
+    }
+    let team = match mode {
+        GameMode::Classic => {
+            (player.role == Role::Shower || field.is_used || full_key).then(|| field.team.clone())
+        }
+        GameMode::Duet if found_green(field) => Some(Team::Green),
+        GameMode::Duet => Some(field.key_for(&player.team)),
+    };
+    player_field(field, team)
+}
+
+/// What someone who does not play the game may see of a card: its color once
+/// it is revealed, or when `full_key` shows them the key. On a Duet board that
+/// is the red side's key, with green cards shown once anyone has found them.
+pub fn field_for_onlooker(field: &Field, mode: &GameMode, full_key: bool) -> PlayerField {
+    let team = (field.is_used || full_key).then(|| match mode {
+        GameMode::Classic => field.team.clone(),
+        GameMode::Duet if found_green(field) => Team::Green,
+        GameMode::Duet => field.key_for(&Team::Red),
+    });
+    player_field(field, team)
+}
+
+fn found_green(field: &Field) -> bool {
+    field.is_used
+        && (field.key_for(&Team::Red) == Team::Green || field.key_for(&Team::Blue) == Team::Green)
+}
+
+fn player_field(field: &Field, team: Option<Team>) -> PlayerField {
+    PlayerField {
+        id: field.id,
+        game_id: field.game_id,
+        text: field.text.clone(),
+        is_used: field.is_used,
+        position: field.position,
+        row_index: field.row_index,
+        col_index: field.col_index,
+        team,
+        neutral_for: field.neutral_side(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::Teams,
+        types::{GameStage, TurnPhase},
+    };
+
+    fn board(words: &[&str]) -> Vec<Field> {
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Field {
+                id: i as i32,
+                game_id: 1,
+                team: Team::Neutral,
+                text: text.to_string(),
+                is_used: false,
+                position: i as i32,
+                row_index: 0,
+                col_index: i as i32,
+                created_at: NaiveDateTime::default(),
+                team_b: None,
+                neutral_for: None,
+            })
+            .collect()
+    }
+
+    fn violation(clue: &str, word: &str, strictness: ClueStrictness, language: Language) -> bool {
+        clue_violation(clue, &board(&[word]), &strictness, &language).is_some()
+    }
+
+    #[test]
+    fn rejects_inflected_board_words() {
+        let corpus = [
+            (Language::Polish, "psa", "pies"),
+            (Language::Polish, "psy", "pies"),
+            (Language::Polish, "snu", "sen"),
+            (Language::Polish, "lwa", "lew"),
+            (Language::Polish, "zolw", "żółw"),
+            (Language::Polish, "ŻÓŁWIE", "żółw"),
+            (Language::Polish, "kota", "kot"),
+            (Language::Polish, "domami", "dom"),
+            (Language::English, "runs", "run"),
+            (Language::English, "cats", "cat"),
+            (Language::English, "berries", "berry"),
+            (Language::English, "jumped", "jump"),
+            (Language::English, "Apple", "apple"),
+            (Language::German, "Bären", "Bär"),
+            (Language::German, "baer", "Bär"),
+            (Language::German, "strasse", "Straße"),
+            (Language::German, "Hunde", "Hund"),
+            (Language::German, "Kindern", "Kind"),
+        ];
+        for (language, clue, word) in corpus {
+            assert!(
+                violation(clue, word, ClueStrictness::Stem, language),
+                "{clue} should be rejected for {word}"
+            );
+        }
+    }
+
+    #[test]
+    fn allows_near_misses() {
+        let corpus = [
+            (Language::Polish, "osa", "oś"),
+            (Language::Polish, "pas", "pies"),
+            (Language::Polish, "lato", "lot"),
+            (Language::Polish, "sen", "syn"),
+            (Language::Polish, "ryba", "ryż"),
+            (Language::English, "cart", "cat"),
+            (Language::English, "plane", "plan"),
+            (Language::English, "bus", "bush"),
+            (Language::German, "Rad", "Rat"),
+            (Language::German, "Hut", "Hütte"),
+            (Language::German, "Ende", "Ente"),
+        ];
+        for (language, clue, word) in corpus {
+            assert!(
+                !violation(clue, word, ClueStrictness::Stem, language),
+                "{clue} should be allowed for {word}"
+            );
+        }
+    }
+
+    #[test]
+    fn strictness_levels() {
+        assert!(!violation(
+            "pies",
+            "pies",
+            ClueStrictness::Off,
+            Language::Polish
+        ));
+        assert!(violation(
+            "Pies",
+            "pies",
+            ClueStrictness::Exact,
+            Language::Polish
+        ));
+        assert!(!violation(
+            "psa",
+            "pies",
+            ClueStrictness::Exact,
+            Language::Polish
+        ));
+        assert!(!violation(
+            "plane",
+            "plan",
+            ClueStrictness::Stem,
+            Language::English
+        ));
+        assert!(violation(
+            "plane",
+            "plan",
+            ClueStrictness::Strict,
+            Language::English
+        ));
+        // Overlaps are counted in letters, not bytes
+        assert!(!violation(
+            "źó",
+            "jeźółw",
+            ClueStrictness::Strict,
+            Language::Polish
+        ));
+        assert!(violation(
+            "żółw",
+            "żółwica",
+            ClueStrictness::Strict,
+            Language::Polish
+        ));
+    }
+
+    #[test]
+    fn clue_numbers_are_capped_by_the_cards_left() {
+        let rule = ZeroClueRule::Unlimited;
+        assert_eq!(guesses_for_clue(3, 5, false, &rule, false), Ok(Some(4)));
+        assert_eq!(guesses_for_clue(5, 5, false, &rule, false), Ok(Some(6)));
+        assert!(guesses_for_clue(6, 5, false, &rule, false).is_err());
+        assert!(guesses_for_clue(i32::MAX, 9, false, &rule, false).is_err());
+        assert_eq!(guesses_for_clue(0, 5, false, &rule, false), Ok(None));
+        assert_eq!(guesses_for_clue(0, 5, true, &rule, true), Ok(None));
+    }
+
+    #[test]
+    fn ignores_revealed_cards() {
+        let mut fields = board(&["pies"]);
+        fields[0].is_used = true;
+        let violation = clue_violation("psa", &fields, &ClueStrictness::Strict, &Language::Polish);
+        assert_eq!(violation, None);
+    }
+
+    fn duet_field(red: Team, blue: Team) -> Field {
+        let mut field = board(&["word"]).remove(0);
+        field.team = red;
+        field.team_b = Some(blue.to_string());
+        field
+    }
+
+    fn player(team: Team, role: Role) -> Player {
+        Player {
+            id: 1,
+            room_id: 1,
+            username: "player".to_string(),
+            team,
+            role,
+            created_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn each_duet_side_reads_its_own_key() {
+        let field = duet_field(Team::Green, Team::Black);
+        assert_eq!(field.key_for(&Team::Red), Team::Green);
+        assert_eq!(field.key_for(&Team::Blue), Team::Black);
+        assert_eq!(duet_reveal(&field, &Team::Red), DuetReveal::Green);
+        assert_eq!(duet_reveal(&field, &Team::Blue), DuetReveal::Assassin);
+
+        // A classic card has the same color for everyone
+        let classic = board(&["word"]).remove(0);
+        assert_eq!(classic.key_for(&Team::Blue), Team::Neutral);
+    }
+
+    #[test]
+    fn a_shared_assassin_loses_from_either_side() {
+        let field = duet_field(Team::Black, Team::Black);
+        assert_eq!(duet_reveal(&field, &Team::Red), DuetReveal::Assassin);
+        assert_eq!(duet_reveal(&field, &Team::Blue), DuetReveal::Assassin);
+    }
+
+    #[test]
+    fn bystanders_stay_open_for_the_other_side() {
+        let mut field = duet_field(Team::Neutral, Team::Green);
+        assert_eq!(duet_reveal(&field, &Team::Red), DuetReveal::Bystander);
+
+        field.neutral_for = Some(Team::Red.to_string());
+        assert_eq!(duet_reveal(&field, &Team::Blue), DuetReveal::Green);
+
+        let mut field = duet_field(Team::Neutral, Team::Neutral);
+        field.neutral_for = Some(Team::Red.to_string());
+        assert_eq!(
+            duet_reveal(&field, &Team::Blue),
+            DuetReveal::BystanderForBoth
+        );
+    }
+
+    #[test]
+    fn duet_boards_clear_once_every_green_is_found() {
+        let mut fields = vec![
+            duet_field(Team::Green, Team::Neutral),
+            duet_field(Team::Neutral, Team::Green),
+            duet_field(Team::Neutral, Team::Neutral),
+        ];
+        assert!(!duet_board_cleared(&fields));
+        fields[0].is_used = true;
+        assert!(!duet_board_cleared(&fields));
+        fields[1].is_used = true;
+        assert!(duet_board_cleared(&fields));
+    }
+
+    #[test]
+    fn duet_sides_take_turns_until_one_has_no_greens_left() {
+        let mut fields = vec![
+            duet_field(Team::Green, Team::Neutral),
+            duet_field(Team::Neutral, Team::Green),
+        ];
+        assert_eq!(next_duet_clue_giver(&Team::Red, &fields), Team::Blue);
+        assert_eq!(next_duet_clue_giver(&Team::Blue, &fields), Team::Red);
+        fields[1].is_used = true;
+        assert_eq!(next_duet_clue_giver(&Team::Red, &fields), Team::Red);
+    }
+
+    fn game(teams: &[Team]) -> Game {
+        Game {
+            id: 1,
+            room_id: 1,
+            game_stage: GameStage::InProgress,
+            current_team: teams[0].clone(),
+            starting_team: teams[0].clone(),
+            winner: None,
+            points: 0,
+            board_rows: 5,
+            board_cols: 5,
+            seed: None,
+            turn_phase: TurnPhase::Guess,
+            guesses_remaining: None,
+            turns_remaining: None,
+            turn_deadline: None,
+            teams: Teams(teams.to_vec()),
+            eliminated_teams: Teams::default(),
+            started_at: None,
+            finished_at: None,
+            created_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn turns_skip_eliminated_teams() {
+        let mut game = game(&[Team::Yellow, Team::Red, Team::Blue]);
+        assert_eq!(game.next_team(), Team::Red);
+        game.eliminated_teams = Teams(vec![Team::Red]);
+        assert_eq!(game.teams_in_play(), [Team::Yellow, Team::Blue]);
+        assert_eq!(game.next_team(), Team::Blue);
+        game.current_team = Team::Blue;
+        assert_eq!(game.next_team(), Team::Yellow);
+        // The eliminated team's own turn passes on to the next team in play
+        game.current_team = Team::Red;
+        assert_eq!(game.next_team(), Team::Blue);
+        assert_eq!(game.last_team_standing(), None);
+    }
+
+    #[test]
+    fn the_last_team_standing_wins() {
+        let mut game = game(&[Team::Red, Team::Blue, Team::Yellow]);
+        game.eliminated_teams = Teams(vec![Team::Yellow, Team::Red]);
+        assert_eq!(game.teams_in_play(), [Team::Blue]);
+        assert_eq!(game.last_team_standing(), Some(Team::Blue));
+    }
+
+    #[test]
+    fn the_assassin_ends_a_two_team_game() {
+        let mut game = game(&[Team::Red, Team::Blue]);
+        assert_eq!(game.last_team_standing(), None);
+        game.eliminated_teams = Teams(vec![Team::Red]);
+        assert_eq!(game.last_team_standing(), Some(Team::Blue));
+    }
+
+    #[test]
+    fn duet_guessers_are_the_side_without_the_clue() {
+        let mut game = game(&[Team::Red, Team::Blue]);
+        game.turns_remaining = Some(9);
+        let blue = player(Team::Blue, Role::Shower);
+        assert!(guesses_this_turn(&blue, &game, &GameMode::Duet));
+        assert!(!guesses_this_turn(&blue, &game, &GameMode::Classic));
+        game.current_team = Team::Blue;
+        assert!(!guesses_this_turn(&blue, &game, &GameMode::Duet));
+        let spectator = player(Team::Neutral, Role::Spectator);
+        assert!(!guesses_this_turn(&spectator, &game, &GameMode::Duet));
+    }
+
+    #[test]
+    fn duet_players_see_their_own_key_and_found_greens() {
+        let field = duet_field(Team::Black, Team::Green);
+        let red = player(Team::Red, Role::Guesser);
+        let blue = player(Team::Blue, Role::Shower);
+        let spectator = player(Team::Neutral, Role::Spectator);
+        let mode = GameMode::Duet;
+        assert_eq!(
+            field_for_player(&field, &red, &mode, false).team,
+            Some(Team::Black)
+        );
+        assert_eq!(
+            field_for_player(&field, &blue, &mode, false).team,
+            Some(Team::Green)
+        );
+        assert_eq!(
+            field_for_player(&field, &spectator, &mode, false).team,
+            None
+        );
+        assert_eq!(
+            field_for_player(&field, &spectator, &mode, true).team,
+            Some(Team::Black)
+        );
+
+        let mut found = field;
+        found.is_used = true;
+        assert_eq!(
+            field_for_player(&found, &red, &mode, false).team,
+            Some(Team::Green)
+        );
+        assert_eq!(
+            field_for_onlooker(&found, &mode, false).team,
+            Some(Team::Green)
+        );
+
+        let mut bystander = duet_field(Team::Neutral, Team::Green);
+        bystander.neutral_for = Some(Team::Red.to_string());
+        let view = field_for_onlooker(&bystander, &mode, false);
+        assert_eq!((view.team, view.neutral_for), (None, Some(Team::Red)));
+    }
+
+    #[test]
+    fn classic_guessers_and_onlookers_only_see_revealed_cards() {
+        let mut field = board(&["word"]).remove(0);
+        field.team = Team::Red;
+        let mode = GameMode::Classic;
+        let shower = player(Team::Blue, Role::Shower);
+        let guesser = player(Team::Red, Role::Guesser);
+        let spectator = player(Team::Neutral, Role::Spectator);
+        assert_eq!(
+            field_for_player(&field, &shower, &mode, false).team,
+            Some(Team::Red)
+        );
+        assert_eq!(field_for_player(&field, &guesser, &mode, false).team, None);
+        assert_eq!(
+            field_for_player(&field, &spectator, &mode, false).team,
+            None
+        );
+        assert_eq!(field_for_onlooker(&field, &mode, false).team, None);
+        assert_eq!(
+            field_for_onlooker(&field, &mode, true).team,
+            Some(Team::Red)
+        );
+
+        field.is_used = true;
+        assert_eq!(
+            field_for_player(&field, &guesser, &mode, false).team,
+            Some(Team::Red)
+        );
+        assert_eq!(
+            field_for_onlooker(&field, &mode, false).team,
+            Some(Team::Red)
+        );
+    }
+
+    #[test]
+    fn consensus_needs_a_share_of_the_guessers_rounded_up() {
+        assert_eq!(votes_needed(1, 50), 1);
+        assert_eq!(votes_needed(2, 50), 1);
+        assert_eq!(votes_needed(2, 51), 2);
+        assert_eq!(votes_needed(3, 50), 2);
+        assert_eq!(votes_needed(3, 67), 3);
+        assert_eq!(votes_needed(3, 100), 3);
+        // Out of range percentages are clamped and nobody left still needs one vote
+        assert_eq!(votes_needed(3, 0), 1);
+        assert_eq!(votes_needed(3, 250), 3);
+        assert_eq!(votes_needed(0, 50), 1);
+    }
+
+    #[test]
+    fn guessers_who_leave_mid_vote_only_count_if_they_voted() {
+        let game = game(&[Team::Red, Team::Blue]);
+        let guesser = |id, presence| PlayerWithPresence {
+            player: Player {
+                id,
+                ..player(Team::Red, Role::Guesser)
+            },
+            presence,
+        };
+        let vote = |player_id| FieldVote {
+            id: player_id,
+            game_id: 1,
+            field_id: 7,
+            player_id,
+            created_at: NaiveDateTime::default(),
+        };
+        let mut players = vec![
+            guesser(1, Presence::Online),
+            guesser(2, Presence::Away),
+            guesser(3, Presence::Online),
+            PlayerWithPresence {
+                player: player(Team::Blue, Role::Guesser),
+                presence: Presence::Online,
+            },
+        ];
+        let votes = vec![vote(1), vote(3)];
+        let voters = consensus_voters(&players, &votes, &game, &GameMode::Classic);
+        assert_eq!(voters, 3);
+        assert!(votes.len() < votes_needed(voters, 100));
+
+        // The guesser who had not voted yet goes offline, the others decide
+        players[1].presence = Presence::Offline;
+        let voters = consensus_voters(&players, &votes, &game, &GameMode::Classic);
+        assert_eq!(voters, 2);
+        assert!(votes.len() >= votes_needed(voters, 100));
+
+        // One who already voted keeps counting
+        players[0].presence = Presence::Offline;
+        assert_eq!(
+            consensus_voters(&players, &votes, &game, &GameMode::Classic),
+            2
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use std::{error::Error, future::Future, pin::Pin, sync::Arc};
+
+use chrono::NaiveDateTime;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+use crate::{
+    game_flow::{eliminate_team, end_game, end_turn},
+    models::{Game, TimeBank},
+    my_state::MyState,
+    repositories::{
+        game_repository::{get_game_by_id, get_games_with_turn_deadline, set_turn_deadline},
+        room_repository::get_room_by_id,
+        time_bank_repository::{get_time_bank, get_time_banks_for_game, set_time_bank},
+    },
+    types::{GameMode, GameStage, TurnTimer},
+};
+
+pub const MIN_TURN_SECONDS: i32 = 15;
+pub const MAX_TURN_SECONDS: i32 = 600;
+pub const MIN_TIME_BANK_SECONDS: i32 = 60;
+pub const MAX_TIME_BANK_SECONDS: i32 = 3600;
+
+/// Milliseconds left until `deadline`, never negative.
+pub fn remaining_ms(deadline: NaiveDateTime, now: NaiveDateTime) -> i64 {
+    (deadline - now).num_milliseconds().max(0)
+}
+
+/// How long a turn may run: what is left in the team's time bank on a chess
+/// clock, otherwise the room's turn time. `None` when turns are not timed.
+pub fn turn_length(time_bank: Option<&TimeBank>, turn_seconds: i32) -> Option<chrono::Duration> {
+    match time_bank {
+        Some(time_bank) => Some(chrono::Duration::milliseconds(time_bank.remaining_ms)),
+        None if turn_seconds > 0 => Some(chrono::Duration::seconds(turn_seconds as i64)),
+        None => None,
+    }
+}
+
+/// What happens when a turn runs out of time.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TurnTimeout {
+    PassTurn,
+    EliminateTeam,
+    EndGame,
+}
+
+/// On a chess clock running out of time loses the game: a classic team is
+/// knocked out and Duet players lose together. Otherwise the turn passes.
+pub fn turn_timeout(chess_clock: bool, mode: &GameMode) -> TurnTimeout {
+    match (chess_clock, mode) {
+        (false, _) => TurnTimeout::PassTurn,
+        (true, GameMode::Classic) => TurnTimeout::EliminateTeam,
+        (true, GameMode::Duet) => TurnTimeout::EndGame,
+    }
+}
+
+/// Counts the running team's bank down to `now`; the other banks are
+/// stopped and keep their stored time.
+pub fn count_down_time_banks(time_banks: &mut [TimeBank], game: &Game, now: NaiveDateTime) {
+    let Some(deadline) = game.turn_deadline else {
+        return;
+    };
+    for time_bank in time_banks {
+        if time_bank.team == game.current_team {
+            time_bank.remaining_ms = remaining_ms(deadline, now);
+        }
+    }
+}
+
+/// Whether the game's turns are on the clock. A game waiting in the lobby
+/// starts its first turn's clock once it gets under way, and a finished one
+/// has no turns left.
+pub fn turns_are_timed(game: &Game) -> bool {
+    matches!(game.game_stage, GameStage::InProgress)
+}
+
+/// Whether the timer that was armed for `deadline` still ends the game's turn,
+/// rather than a turn that ended some other way or a game no longer running.
+pub fn turn_expires(game: &Game, deadline: NaiveDateTime) -> bool {
+    turns_are_timed(game) && game.turn_deadline == Some(deadline)
+}
+
+/// Gives the turn that just began its deadline and tells the room when it
+/// runs out. With a chess clock the deadline is when the team's time bank
+/// empties, otherwise it is the room's turn time away. Returns the updated game.
+pub async fn start_turn_timer(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+) -> Result<Game, Box<dyn Error>> {
+    let Some(game) = get_game_by_id(state.clone(), game_id).await? else {
+        return Err("Game not found".into());
+    };
+    if !turns_are_timed(&game) {
+        return Ok(game);
+    }
+    let room = get_room_by_id(state.clone(), game.room_id).await?;
+    let time_bank = get_time_bank(state.clone(), game_id, &game.current_team).await?;
+    let Some(turn_length) = turn_length(time_bank.as_ref(), room.turn_seconds) else {
+        return Ok(game);
+    };
+    let deadline = state.read().await.clock.now() + turn_length;
+    // Arm the timer with the stored deadline, which is what expiry compares against
+    let game = set_turn_deadline(state.clone(), game_id, Some(deadline)).await?;
+    let Some(deadline) = game.turn_deadline else {
+        return Ok(game);
+    };
+
+    state
+        .read()
+        .await
+        .io
+        .to(game.room_id.to_string())
+        .emit("turn-timer-started", &TurnTimer { game_id, deadline })
+        .ok();
+    arm_turn_timer(state, game_id, deadline);
+    Ok(game)
+}
+
+/// Passes the turn once `deadline` is reached, unless the turn ended some
+/// other way before that.
+pub fn arm_turn_timer(state: Arc<RwLock<MyState>>, game_id: i32, deadline: NaiveDateTime) {
+    tokio::spawn(async move {
+        let clock = state.read().await.clock.clone();
+        clock.sleep_until(deadline).await;
+        if let Err(e) = expire_turn(state, game_id, deadline).await {
+            error!("Turn timer of game {} failed: {}", game_id, e);
+        }
+    });
+}
+
+// Boxed because ending the turn starts the next timer, which makes the
+// future recursive
+fn expire_turn(
+    state: Arc<RwLock<MyState>>,
+    game_id: i32,
+    deadline: NaiveDateTime,
+) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> {
+    Box::pin(async move {
+        let game = get_game_by_id(state.clone(), game_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(game) = game else {
+            return Ok(());
+        };
+        if !turn_expires(&game, deadline) {
+            return Ok(());
+        }
+
+        state
+            .read()
+            .await
+            .io
+            .to(game.room_id.to_string())
+            .emit("turn-timed-out", &game)
+            .ok();
+        let time_bank = get_time_bank(state.clone(), game_id, &game.current_team)
+            .await
+            .map_err(|e| e.to_string())?;
+        let room = get_room_by_id(state.clone(), game.room_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        match turn_timeout(time_bank.is_some(), &room.game_mode) {
+            TurnTimeout::PassTurn => end_turn(state, game_id).await,
+            TurnTimeout::EliminateTeam => eliminate_team(state, game_id, &game.current_team).await,
+            TurnTimeout::EndGame => end_game(state, game_id, None).await,
+        }
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    })
+}
+
+/// Re-arms the timers of running games after a restart. Deadlines that passed
+/// while the server was down expire right away.
+pub async fn restore_turn_timers(state: Arc<RwLock<MyState>>) -> Result<(), Box<dyn Error>> {
+    let games = get_games_with_turn_deadline(state.clone()).await?;
+    info!("Restoring {} turn timers", games.len());
+    for game in games {
+        if let Some(deadline) = game.turn_deadline {
+            arm_turn_timer(state.clone(), game.id, deadline);
+        }
+    }
+    Ok(())
+}
+
+/// Writes the running team's time back to its bank when its turn stops.
+/// Does nothing for games without a chess clock.
+pub async fn charge_time_bank(
+    state: Arc<RwLock<MyState>>,
+    game: &Game,
+) -> Result<(), Box<dyn Error>> {
+    let Some(deadline) = game.turn_deadline else {
+        return Ok(());
+    };
+    if get_time_bank(state.clone(), game.id, &game.current_team)
+        .await?
+        .is_none()
+    {
+        return Ok(());
+    }
+    let now = state.read().await.clock.now();
+    set_time_bank(
+        state,
+        game.id,
+        &game.current_team,
+        remaining_ms(deadline, now),
+    )
+    .await
+}
+
+/// The game's time banks with the running team's time counted down to now.
+pub async fn current_time_banks(
+    state: Arc<RwLock<MyState>>,
+    game: &Game,
+) -> Result<Vec<TimeBank>, Box<dyn Error>> {
+    let mut time_banks = get_time_banks_for_game(state.clone(), game.id).await?;
+    let now = state.read().await.clock.now();
+    count_down_time_banks(&mut time_banks, game, now);
+    Ok(time_banks)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::{
+        clock::{Clock, FakeClock},
+        models::{Team, Teams},
+        types::TurnPhase,
+    };
+
+    fn at(seconds: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 9, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, seconds)
+            .unwrap()
+    }
+
+    fn game(current_team: Team, turn_deadline: Option<NaiveDateTime>) -> Game {
+        Game {
+            id: 1,
+            room_id: 1,
+            game_stage: GameStage::InProgress,
+            current_team,
+            starting_team: Team::Red,
+            winner: None,
+            points: 0,
+            board_rows: 5,
+            board_cols: 5,
+            seed: None,
+            turn_phase: TurnPhase::Clue,
+            guesses_remaining: None,
+            turns_remaining: None,
+            turn_deadline,
+            teams: Teams(vec![Team::Red, Team::Blue]),
+            eliminated_teams: Teams::default(),
+            started_at: Some(at(0)),
+            finished_at: None,
+            created_at: at(0),
+        }
+    }
+
+    fn time_bank(team: Team, remaining_ms: i64) -> TimeBank {
+        TimeBank {
+            id: 1,
+            game_id: 1,
+            team,
+            remaining_ms,
+            updated_at: at(0),
+        }
+    }
+
+    #[test]
+    fn remaining_time_never_goes_negative() {
+        assert_eq!(remaining_ms(at(30), at(10)), 20_000);
+        assert_eq!(remaining_ms(at(30), at(30)), 0);
+        assert_eq!(remaining_ms(at(30), at(45)), 0);
+    }
+
+    #[test]
+    fn turns_run_for_the_time_bank_or_the_room_time() {
+        let bank = time_bank(Team::Red, 90_500);
+        assert_eq!(
+            turn_length(Some(&bank), 60),
+            Some(chrono::Duration::milliseconds(90_500))
+        );
+        assert_eq!(turn_length(None, 60), Some(chrono::Duration::seconds(60)));
+        assert_eq!(turn_length(None, 0), None);
+    }
+
+    #[test]
+    fn only_the_running_bank_counts_down() {
+        let mut banks = vec![time_bank(Team::Red, 60_000), time_bank(Team::Blue, 45_000)];
+        count_down_time_banks(&mut banks, &game(Team::Red, Some(at(50))), at(20));
+        assert_eq!(banks[0].remaining_ms, 30_000);
+        assert_eq!(banks[1].remaining_ms, 45_000);
+
+        // A stopped clock leaves every bank as stored
+        let mut banks = vec![time_bank(Team::Red, 60_000)];
+        count_down_time_banks(&mut banks, &game(Team::Red, None), at(20));
+        assert_eq!(banks[0].remaining_ms, 60_000);
+    }
+
+    #[test]
+    fn an_empty_bank_loses_the_game() {
+        let empty = time_bank(Team::Red, 0);
+        assert_eq!(
+            turn_length(Some(&empty), 60),
+            Some(chrono::Duration::zero())
+        );
+        let mut banks = vec![time_bank(Team::Red, 5_000)];
+        count_down_time_banks(&mut banks, &game(Team::Red, Some(at(5))), at(9));
+        assert_eq!(banks[0].remaining_ms, 0);
+
+        assert_eq!(
+            turn_timeout(true, &GameMode::Classic),
+            TurnTimeout::EliminateTeam
+        );
+        assert_eq!(turn_timeout(true, &GameMode::Duet), TurnTimeout::EndGame);
+        assert_eq!(
+            turn_timeout(false, &GameMode::Classic),
+            TurnTimeout::PassTurn
+        );
+        assert_eq!(turn_timeout(false, &GameMode::Duet), TurnTimeout::PassTurn);
+    }
+
+    #[tokio::test]
+    async fn timers_fire_when_the_clock_reaches_the_deadline() {
+        let clock = Arc::new(FakeClock::new(at(0)));
+        let timer = tokio::spawn(clock.sleep_until(at(30)));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!timer.is_finished());
+
+        clock.advance(chrono::Duration::seconds(29));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!timer.is_finished());
+
+        clock.advance(chrono::Duration::seconds(1));
+        tokio::time::timeout(Duration::from_secs(1), timer)
+            .await
+            .expect("timer did not fire")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn lobby_deadlines_do_not_fire() {
+        let clock = FakeClock::new(at(0));
+        let mut lobby = game(Team::Red, Some(at(30)));
+        lobby.game_stage = GameStage::WaitingForPlayers;
+        assert!(!turns_are_timed(&lobby));
+
+        clock.advance(chrono::Duration::seconds(45));
+        tokio::time::timeout(Duration::from_secs(1), clock.sleep_until(at(30)))
+            .await
+            .expect("timer did not wake up");
+        assert!(!turn_expires(&lobby, at(30)));
+
+        // The same deadline ends the turn once the game is under way
+        lobby.game_stage = GameStage::InProgress;
+        assert!(turn_expires(&lobby, at(30)));
+        assert!(!turn_expires(&lobby, at(29)));
+        lobby.game_stage = GameStage::Finished;
+        assert!(!turn_expires(&lobby, at(30)));
+    }
+
+    #[tokio::test]
+    async fn passed_deadlines_fire_right_away() {
+        let clock = FakeClock::new(at(40));
+        tokio::time::timeout(Duration::from_secs(1), clock.sleep_until(at(30)))
+            .await
+            .expect("timer did not fire");
+    }
+}
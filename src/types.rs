@@ -1,5 +1,18 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    board::{MAX_AVOID_RECENT_GAMES, MAX_BOARD_SIDE, MAX_TEAMS, MIN_BOARD_SIDE, MIN_TEAMS},
+    models::{
+        CommunityWordPack, Game, Player, PlayerScore, ReactionCount, Room, Team, TeamScore,
+        TimeBank,
+    },
+    rules::MAX_SPECTATOR_KEY_DELAY_SECONDS,
+    turn_timer::{
+        MAX_TIME_BANK_SECONDS, MAX_TURN_SECONDS, MIN_TIME_BANK_SECONDS, MIN_TURN_SECONDS,
+    },
+    word_packs::WordPack,
+};
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct JoinRoomRequest {
     pub player_id: i32,
@@ -7,7 +20,318 @@ pub struct JoinRoomRequest {
     pub username: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RoomSettings {
+    pub board_rows: Option<i32>,
+    pub board_cols: Option<i32>,
+    pub scoring_mode: Option<ScoringMode>,
+    pub word_pack: Option<String>,
+    pub custom_words: Option<Vec<String>>,
+    pub custom_word_ratio: Option<i32>,
+    pub language: Option<Language>,
+    pub avoid_recent_games: Option<i32>,
+    pub difficulty: Option<Difficulty>,
+    pub clue_strictness: Option<ClueStrictness>,
+    pub zero_clue_rule: Option<ZeroClueRule>,
+    pub allow_unlimited_clues: Option<bool>,
+    /// Seconds per turn, 0 turns the timer off.
+    pub turn_seconds: Option<i32>,
+    /// Seconds per team for the whole game, 0 turns the chess clock off.
+    pub time_bank_seconds: Option<i32>,
+    pub game_mode: Option<GameMode>,
+    /// Teams in a classic game, two or three.
+    pub team_count: Option<i32>,
+    pub spectators_see_key: Option<bool>,
+    /// Seconds into the game before spectators see the whole key.
+    pub spectator_key_delay_seconds: Option<i32>,
+    pub reveal_mode: Option<RevealMode>,
+    /// Share of the guessers that has to vote for a card in consensus mode.
+    pub consensus_percent: Option<i32>,
+    /// Keeps showers out of the chat while guessers are picking cards.
+    pub mute_showers_while_guessing: Option<bool>,
+    /// Seed for the next board only, it is not stored on the room.
+    pub seed: Option<i64>,
+    /// Starting team for the next board only. Together with `seed` it replays
+    /// a game whose starting team was not the seeded one, such as a rematch.
+    pub starting_team: Option<Team>,
+}
+
+impl RoomSettings {
+    /// Fills in whichever of `language` and `word_pack` is missing from the
+    /// other, so a room never ends up with a pack in a different language.
+    pub fn resolve_language(&mut self, word_packs: &[WordPack]) -> Result<(), String> {
+        match (&self.word_pack, &self.language) {
+            (Some(name), language) => {
+                let Some(pack) = word_packs.iter().find(|pack| &pack.name == name) else {
+                    return Err(format!("Unknown word pack {name}"));
+                };
+                if language
+                    .as_ref()
+                    .is_some_and(|language| *language != pack.language)
+                {
+                    return Err(format!(
+                        "Word pack {name} is not in {}",
+                        pack.language.to_string()
+                    ));
+                }
+                self.language = Some(pack.language.clone());
+            }
+            (None, Some(language)) => {
+                let Some(pack) = word_packs.iter().find(|pack| pack.language == *language) else {
+                    return Err(format!("No word pack in {}", language.to_string()));
+                };
+                self.word_pack = Some(pack.name.clone());
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    /// The settings `room` ends up with once these are applied, so rules
+    /// spanning several settings are checked against the stored ones too.
+    pub fn merged_with(&self, room: &Room) -> RoomSettings {
+        RoomSettings {
+            board_rows: self.board_rows.or(Some(room.board_rows)),
+            board_cols: self.board_cols.or(Some(room.board_cols)),
+            scoring_mode: self
+                .scoring_mode
+                .clone()
+                .or(Some(room.scoring_mode.clone())),
+            // The stored pack was checked when it was picked and may have
+            // been withdrawn since, the board then falls back to a built-in one
+            word_pack: self.word_pack.clone(),
+            custom_words: self.custom_words.clone(),
+            custom_word_ratio: self.custom_word_ratio.or(Some(room.custom_word_ratio)),
+            language: self.language.clone(),
+            avoid_recent_games: self.avoid_recent_games.or(Some(room.avoid_recent_games)),
+            difficulty: self.difficulty.clone().or(Some(room.difficulty.clone())),
+            clue_strictness: self
+                .clue_strictness
+                .clone()
+                .or(Some(room.clue_strictness.clone())),
+            zero_clue_rule: self
+                .zero_clue_rule
+                .clone()
+                .or(Some(room.zero_clue_rule.clone())),
+            allow_unlimited_clues: self
+                .allow_unlimited_clues
+                .or(Some(room.allow_unlimited_clues)),
+            turn_seconds: self.turn_seconds.or(Some(room.turn_seconds)),
+            time_bank_seconds: self.time_bank_seconds.or(Some(room.time_bank_seconds)),
+            game_mode: self.game_mode.clone().or(Some(room.game_mode.clone())),
+            team_count: self.team_count.or(Some(room.team_count)),
+            spectators_see_key: self.spectators_see_key.or(Some(room.spectators_see_key)),
+            spectator_key_delay_seconds: self
+                .spectator_key_delay_seconds
+                .or(Some(room.spectator_key_delay_seconds)),
+            reveal_mode: self.reveal_mode.clone().or(Some(room.reveal_mode.clone())),
+            consensus_percent: self.consensus_percent.or(Some(room.consensus_percent)),
+            mute_showers_while_guessing: self
+                .mute_showers_while_guessing
+                .or(Some(room.mute_showers_while_guessing)),
+            seed: self.seed,
+            starting_team: self.starting_team.clone(),
+        }
+    }
+
+    pub fn validate(&self, word_packs: &[WordPack]) -> Result<(), String> {
+        for side in [self.board_rows, self.board_cols].into_iter().flatten() {
+            if !(MIN_BOARD_SIDE..=MAX_BOARD_SIDE).contains(&side) {
+                return Err(format!(
+                    "Board dimensions must be between {MIN_BOARD_SIDE} and {MAX_BOARD_SIDE}, got {side}"
+                ));
+            }
+        }
+        if let Some(name) = &self.word_pack {
+            if !word_packs.iter().any(|pack| &pack.name == name) {
+                return Err(format!("Unknown word pack {name}"));
+            }
+        }
+        if let Some(ratio) = self.custom_word_ratio {
+            if !(0..=100).contains(&ratio) {
+                return Err("Custom word ratio must be between 0 and 100".to_string());
+            }
+        }
+        if let Some(games) = self.avoid_recent_games {
+            if !(0..=MAX_AVOID_RECENT_GAMES).contains(&games) {
+                return Err(format!(
+                    "Recent games to avoid must be between 0 and {MAX_AVOID_RECENT_GAMES}"
+                ));
+            }
+        }
+        if let Some(seconds) = self.turn_seconds {
+            if seconds != 0 && !(MIN_TURN_SECONDS..=MAX_TURN_SECONDS).contains(&seconds) {
+                return Err(format!(
+                    "Turn timer must be 0 or between {MIN_TURN_SECONDS} and {MAX_TURN_SECONDS} seconds"
+                ));
+            }
+        }
+        if let Some(seconds) = self.time_bank_seconds {
+            if seconds != 0 && !(MIN_TIME_BANK_SECONDS..=MAX_TIME_BANK_SECONDS).contains(&seconds) {
+                return Err(format!(
+                    "Time bank must be 0 or between {MIN_TIME_BANK_SECONDS} and {MAX_TIME_BANK_SECONDS} seconds"
+                ));
+            }
+        }
+        if let Some(count) = self.team_count {
+            if !(MIN_TEAMS..=MAX_TEAMS).contains(&count) {
+                return Err(format!(
+                    "Team count must be between {MIN_TEAMS} and {MAX_TEAMS}"
+                ));
+            }
+            if count != 2 && self.game_mode == Some(GameMode::Duet) {
+                return Err("Duet is played by two sides".to_string());
+            }
+        }
+        if let Some(seconds) = self.spectator_key_delay_seconds {
+            if !(0..=MAX_SPECTATOR_KEY_DELAY_SECONDS).contains(&seconds) {
+                return Err(format!(
+                    "Spectator key delay must be between 0 and {MAX_SPECTATOR_KEY_DELAY_SECONDS} seconds"
+                ));
+            }
+        }
+        if let Some(percent) = self.consensus_percent {
+            if !(1..=100).contains(&percent) {
+                return Err("Consensus percent must be between 1 and 100".to_string());
+            }
+        }
+        if self.turn_seconds.unwrap_or(0) > 0 && self.time_bank_seconds.unwrap_or(0) > 0 {
+            return Err("Use either a turn timer or a time bank, not both".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct RematchRequest {
+    pub swap_colors: bool,
+    pub rotate_showers: bool,
+    pub seed: Option<i64>,
+    /// Overrides the team that would open the rematch, see `RoomSettings`.
+    pub starting_team: Option<Team>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GiveClueRequest {
+    pub word: String,
+    pub number: i32,
+    /// Lets the team guess as often as it likes; `number` is then ignored.
+    #[serde(default)]
+    pub unlimited: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChatMessageRequest {
+    pub channel: ChatChannel,
+    pub text: String,
+}
+
+/// A reaction to either a card or a player, `field_id` or `target_player_id`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReactionRequest {
+    pub reaction: ReactionKind,
+    pub field_id: Option<i32>,
+    pub target_player_id: Option<i32>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
+pub struct MarkerRequest {
+    pub kind: MarkerKind,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WordPackRequest {
+    pub name: String,
+    pub language: Language,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub words: Vec<String>,
+    pub author: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ForkWordPackRequest {
+    pub author: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreatedWordPack {
+    pub word_pack: CommunityWordPack,
+    pub edit_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommunityWordPackWithWords {
+    #[serde(flatten)]
+    pub word_pack: CommunityWordPack,
+    pub words: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct LanguageFilter {
+    pub language: Option<Language>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RoomScore {
+    pub teams: Vec<TeamScore>,
+    pub players: Vec<PlayerScore>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlayerWithPresence {
+    #[serde(flatten)]
+    pub player: Player,
+    pub presence: Presence,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PresenceUpdate {
+    pub player_id: i32,
+    pub presence: Presence,
+}
+
+/// What the room gets to see once a game is over.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GameSummary {
+    pub game: Game,
+    pub reactions: Vec<ReactionCount>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TurnTimer {
+    pub game_id: i32,
+    pub deadline: chrono::NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RoomSnapshot {
+    pub room: Room,
+    pub game: Option<Game>,
+    pub players: Vec<PlayerWithPresence>,
+    pub spectators: Vec<PlayerWithPresence>,
+    pub score: RoomScore,
+    /// Chess clock time left per team in the current game, empty without one.
+    pub time_banks: Vec<TimeBank>,
+}
+
+/// A card as one player may see it, `team` is `None` while its color is
+/// hidden from them.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlayerField {
+    pub id: i32,
+    pub game_id: i32,
+    pub text: String,
+    pub is_used: bool,
+    pub position: i32,
+    pub row_index: i32,
+    pub col_index: i32,
+    pub team: Option<Team>,
+    pub neutral_for: Option<Team>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum GameStage {
     #[serde(rename = "waiting_for_players")]
     WaitingForPlayers,
@@ -52,6 +376,8 @@ pub enum Role {
     Shower,
     #[serde(rename = "guesser")]
     Guesser,
+    #[serde(rename = "spectator")]
+    Spectator,
 }
 
 impl Role {
@@ -59,6 +385,7 @@ impl Role {
         match self {
             Role::Shower => "shower".to_string(),
             Role::Guesser => "guesser".to_string(),
+            Role::Spectator => "spectator".to_string(),
         }
     }
 }
@@ -68,8 +395,506 @@ impl From<String> for Role {
         match s.as_str() {
             "shower" => Role::Shower,
             "guesser" => Role::Guesser,
+            "spectator" => Role::Spectator,
             _ => panic!("Invalid role"),
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ScoringMode {
+    #[serde(rename = "wins")]
+    Wins,
+    #[serde(rename = "margin")]
+    Margin,
+}
+
+impl ScoringMode {
+    pub fn to_string(&self) -> String {
+        match self {
+            ScoringMode::Wins => "wins".to_string(),
+            ScoringMode::Margin => "margin".to_string(),
+        }
+    }
+}
+
+impl From<String> for ScoringMode {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "wins" => ScoringMode::Wins,
+            "margin" => ScoringMode::Margin,
+            _ => panic!("Invalid scoring mode"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Language {
+    #[serde(rename = "pl")]
+    Polish,
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "de")]
+    German,
+}
+
+impl Language {
+    pub fn to_string(&self) -> String {
+        match self {
+            Language::Polish => "pl".to_string(),
+            Language::English => "en".to_string(),
+            Language::German => "de".to_string(),
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        match code {
+            "pl" => Some(Language::Polish),
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for Language {
+    fn from(s: String) -> Self {
+        Language::from_code(&s).expect("Invalid language")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum WordPackStatus {
+    #[serde(rename = "draft")]
+    Draft,
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "approved")]
+    Approved,
+    #[serde(rename = "rejected")]
+    Rejected,
+}
+
+impl WordPackStatus {
+    pub fn to_string(&self) -> String {
+        match self {
+            WordPackStatus::Draft => "draft".to_string(),
+            WordPackStatus::Pending => "pending".to_string(),
+            WordPackStatus::Approved => "approved".to_string(),
+            WordPackStatus::Rejected => "rejected".to_string(),
+        }
+    }
+}
+
+impl From<String> for WordPackStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "draft" => WordPackStatus::Draft,
+            "pending" => WordPackStatus::Pending,
+            "approved" => WordPackStatus::Approved,
+            "rejected" => WordPackStatus::Rejected,
+            _ => panic!("Invalid word pack status"),
+        }
+    }
+}
+
+/// How hard a single word in a pack is to clue.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordDifficulty {
+    #[serde(rename = "easy")]
+    Easy,
+    #[serde(rename = "normal")]
+    Normal,
+    #[serde(rename = "hard")]
+    Hard,
+}
+
+impl WordDifficulty {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "easy" => Some(WordDifficulty::Easy),
+            "normal" => Some(WordDifficulty::Normal),
+            "hard" => Some(WordDifficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+/// Classic red against blue, or Duet where two players work together.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum GameMode {
+    #[serde(rename = "classic")]
+    Classic,
+    #[serde(rename = "duet")]
+    Duet,
+}
+
+impl GameMode {
+    pub fn to_string(&self) -> String {
+        match self {
+            GameMode::Classic => "classic".to_string(),
+            GameMode::Duet => "duet".to_string(),
+        }
+    }
+}
+
+impl From<String> for GameMode {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "classic" => GameMode::Classic,
+            "duet" => GameMode::Duet,
+            _ => panic!("Invalid game mode"),
+        }
+    }
+}
+
+/// Whether a guesser's click reveals a card, or the team votes on it first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RevealMode {
+    #[serde(rename = "instant")]
+    Instant,
+    #[serde(rename = "consensus")]
+    Consensus,
+}
+
+impl RevealMode {
+    pub fn to_string(&self) -> String {
+        match self {
+            RevealMode::Instant => "instant".to_string(),
+            RevealMode::Consensus => "consensus".to_string(),
+        }
+    }
+}
+
+impl From<String> for RevealMode {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "instant" => RevealMode::Instant,
+            "consensus" => RevealMode::Consensus,
+            _ => panic!("Invalid reveal mode"),
+        }
+    }
+}
+
+/// Who gets to read a chat message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ChatChannel {
+    /// Everyone in the room, spectators included.
+    #[serde(rename = "all")]
+    All,
+    /// The sender's team only.
+    #[serde(rename = "team")]
+    Team,
+}
+
+impl ChatChannel {
+    pub fn to_string(&self) -> String {
+        match self {
+            ChatChannel::All => "all".to_string(),
+            ChatChannel::Team => "team".to_string(),
+        }
+    }
+}
+
+impl From<String> for ChatChannel {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "all" => ChatChannel::All,
+            "team" => ChatChannel::Team,
+            _ => panic!("Invalid chat channel"),
+        }
+    }
+}
+
+/// The reactions players can send, anything else is rejected.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ReactionKind {
+    #[serde(rename = "cheer")]
+    Cheer,
+    #[serde(rename = "groan")]
+    Groan,
+    #[serde(rename = "laugh")]
+    Laugh,
+    #[serde(rename = "gasp")]
+    Gasp,
+    #[serde(rename = "clap")]
+    Clap,
+    #[serde(rename = "facepalm")]
+    Facepalm,
+}
+
+impl ReactionKind {
+    pub fn to_string(&self) -> String {
+        match self {
+            ReactionKind::Cheer => "cheer".to_string(),
+            ReactionKind::Groan => "groan".to_string(),
+            ReactionKind::Laugh => "laugh".to_string(),
+            ReactionKind::Gasp => "gasp".to_string(),
+            ReactionKind::Clap => "clap".to_string(),
+            ReactionKind::Facepalm => "facepalm".to_string(),
+        }
+    }
+}
+
+impl From<String> for ReactionKind {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "cheer" => ReactionKind::Cheer,
+            "groan" => ReactionKind::Groan,
+            "laugh" => ReactionKind::Laugh,
+            "gasp" => ReactionKind::Gasp,
+            "clap" => ReactionKind::Clap,
+            "facepalm" => ReactionKind::Facepalm,
+            _ => panic!("Invalid reaction"),
+        }
+    }
+}
+
+/// Whether a player has the game open, based on their Socket.IO connections.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Presence {
+    #[serde(rename = "online")]
+    Online,
+    /// Disconnected, but still within the grace period to reconnect.
+    #[serde(rename = "away")]
+    Away,
+    #[serde(rename = "offline")]
+    Offline,
+}
+
+/// What a guesser's mark on a card says to their teammates.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum MarkerKind {
+    /// A plain marker on a card the player is considering.
+    #[serde(rename = "marker")]
+    Marker,
+    /// "I think this one".
+    #[serde(rename = "suggestion")]
+    Suggestion,
+}
+
+impl MarkerKind {
+    pub fn to_string(&self) -> String {
+        match self {
+            MarkerKind::Marker => "marker".to_string(),
+            MarkerKind::Suggestion => "suggestion".to_string(),
+        }
+    }
+}
+
+impl From<String> for MarkerKind {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "marker" => MarkerKind::Marker,
+            "suggestion" => MarkerKind::Suggestion,
+            _ => panic!("Invalid marker kind"),
+        }
+    }
+}
+
+/// The mix of word difficulties a room's boards are drawn with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Difficulty {
+    #[serde(rename = "easy")]
+    Easy,
+    #[serde(rename = "normal")]
+    Normal,
+    #[serde(rename = "hard")]
+    Hard,
+    #[serde(rename = "mixed")]
+    Mixed,
+}
+
+impl Difficulty {
+    pub fn to_string(&self) -> String {
+        match self {
+            Difficulty::Easy => "easy".to_string(),
+            Difficulty::Normal => "normal".to_string(),
+            Difficulty::Hard => "hard".to_string(),
+            Difficulty::Mixed => "mixed".to_string(),
+        }
+    }
+}
+
+impl From<String> for Difficulty {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "easy" => Difficulty::Easy,
+            "normal" => Difficulty::Normal,
+            "hard" => Difficulty::Hard,
+            "mixed" => Difficulty::Mixed,
+            _ => panic!("Invalid difficulty"),
+        }
+    }
+}
+
+/// How closely a clue may resemble an unrevealed word on the board.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ClueStrictness {
+    /// Any clue goes.
+    #[serde(rename = "off")]
+    Off,
+    /// The clue cannot be a board word.
+    #[serde(rename = "exact")]
+    Exact,
+    /// Nor share a stem with one.
+    #[serde(rename = "stem")]
+    Stem,
+    /// Nor contain or be contained in one.
+    #[serde(rename = "strict")]
+    Strict,
+}
+
+impl ClueStrictness {
+    pub fn to_string(&self) -> String {
+        match self {
+            ClueStrictness::Off => "off".to_string(),
+            ClueStrictness::Exact => "exact".to_string(),
+            ClueStrictness::Stem => "stem".to_string(),
+            ClueStrictness::Strict => "strict".to_string(),
+        }
+    }
+}
+
+impl From<String> for ClueStrictness {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "off" => ClueStrictness::Off,
+            "exact" => ClueStrictness::Exact,
+            "stem" => ClueStrictness::Stem,
+            "strict" => ClueStrictness::Strict,
+            _ => panic!("Invalid clue strictness"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum TurnPhase {
+    #[serde(rename = "clue")]
+    Clue,
+    #[serde(rename = "guess")]
+    Guess,
+}
+
+impl TurnPhase {
+    pub fn to_string(&self) -> String {
+        match self {
+            TurnPhase::Clue => "clue".to_string(),
+            TurnPhase::Guess => "guess".to_string(),
+        }
+    }
+}
+
+impl From<String> for TurnPhase {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "clue" => TurnPhase::Clue,
+            "guess" => TurnPhase::Guess,
+            _ => panic!("Invalid turn phase"),
+        }
+    }
+}
+
+/// How many guesses a clue of 0 allows.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ZeroClueRule {
+    #[serde(rename = "unlimited")]
+    Unlimited,
+    #[serde(rename = "one")]
+    One,
+    #[serde(rename = "forbidden")]
+    Forbidden,
+}
+
+impl ZeroClueRule {
+    pub fn to_string(&self) -> String {
+        match self {
+            ZeroClueRule::Unlimited => "unlimited".to_string(),
+            ZeroClueRule::One => "one".to_string(),
+            ZeroClueRule::Forbidden => "forbidden".to_string(),
+        }
+    }
+}
+
+impl From<String> for ZeroClueRule {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "unlimited" => ZeroClueRule::Unlimited,
+            "one" => ZeroClueRule::One,
+            "forbidden" => ZeroClueRule::Forbidden,
+            _ => panic!("Invalid zero clue rule"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room(game_mode: GameMode, team_count: i32, turn_seconds: i32) -> Room {
+        Room {
+            id: 1,
+            board_rows: 5,
+            board_cols: 5,
+            scoring_mode: ScoringMode::Wins,
+            word_pack: "polski".to_string(),
+            custom_word_ratio: 0,
+            language: Language::Polish,
+            avoid_recent_games: 0,
+            difficulty: Difficulty::Mixed,
+            clue_strictness: ClueStrictness::Off,
+            zero_clue_rule: ZeroClueRule::Unlimited,
+            allow_unlimited_clues: false,
+            turn_seconds,
+            time_bank_seconds: 0,
+            game_mode,
+            team_count,
+            spectators_see_key: false,
+            spectator_key_delay_seconds: 0,
+            reveal_mode: RevealMode::Instant,
+            consensus_percent: 50,
+            mute_showers_while_guessing: false,
+            created_at: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn changes_are_checked_against_the_stored_settings() {
+        let timed = room(GameMode::Classic, 2, 60);
+        let time_bank = RoomSettings {
+            time_bank_seconds: Some(300),
+            ..Default::default()
+        };
+        assert!(time_bank.validate(&[]).is_ok());
+        assert!(time_bank.merged_with(&timed).validate(&[]).is_err());
+        let switched_clocks = RoomSettings {
+            turn_seconds: Some(0),
+            ..time_bank
+        };
+        assert!(switched_clocks.merged_with(&timed).validate(&[]).is_ok());
+
+        let three_teams = room(GameMode::Classic, 3, 0);
+        let duet = RoomSettings {
+            game_mode: Some(GameMode::Duet),
+            ..Default::default()
+        };
+        assert!(duet.merged_with(&three_teams).validate(&[]).is_err());
+        let two_teams = RoomSettings {
+            team_count: Some(2),
+            ..duet
+        };
+        assert!(two_teams.merged_with(&three_teams).validate(&[]).is_ok());
+    }
+
+    #[test]
+    fn the_stored_word_pack_is_not_checked_again() {
+        // A withdrawn community pack must not block other changes
+        let mut stored = room(GameMode::Classic, 2, 0);
+        stored.word_pack = "community:7".to_string();
+        let settings = RoomSettings {
+            board_rows: Some(6),
+            ..Default::default()
+        };
+        assert!(settings.merged_with(&stored).validate(&[]).is_ok());
+    }
+}
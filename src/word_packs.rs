@@ -0,0 +1,579 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    normalize::normalize_word,
+    profanity::contains_profanity,
+    types::{Language, WordDifficulty, WordPackRequest},
+};
+
+pub const DEFAULT_WORD_PACKS_DIR: &str = "word_packs";
+pub const DEFAULT_WORD_PACK: &str = "polski";
+pub const MAX_CUSTOM_WORDS: usize = 400;
+pub const MAX_CUSTOM_WORD_LENGTH: usize = 30;
+pub const COMMUNITY_WORD_PACK_PREFIX: &str = "community:";
+pub const MAX_COMMUNITY_WORDS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordPack {
+    pub name: String,
+    pub language: Language,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub words: Vec<String>,
+    /// Sets of closely related words; a board gets at most one word of each.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<Vec<String>>,
+    /// Optional stem per word; words sharing a stem count as one group.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub stems: HashMap<String, String>,
+    /// Words listed by difficulty; words not listed anywhere are normal.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub difficulties: HashMap<WordDifficulty, Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WordPackSummary {
+    pub key: String,
+    pub name: String,
+    pub language: Language,
+    pub tags: Vec<String>,
+    pub word_count: i64,
+    pub usage_count: Option<i32>,
+}
+
+/// Rooms refer to built-in packs by name and to community packs by this key.
+pub fn community_word_pack_key(id: i32) -> String {
+    format!("{COMMUNITY_WORD_PACK_PREFIX}{id}")
+}
+
+pub fn parse_community_word_pack_key(key: &str) -> Option<i32> {
+    key.strip_prefix(COMMUNITY_WORD_PACK_PREFIX)?.parse().ok()
+}
+
+impl WordPack {
+    pub fn summary(&self) -> WordPackSummary {
+        WordPackSummary {
+            key: self.name.clone(),
+            name: self.name.clone(),
+            language: self.language.clone(),
+            tags: self.tags.clone(),
+            word_count: self.words.len() as i64,
+            usage_count: None,
+        }
+    }
+
+    /// Maps every lowercased word to a key shared by all words in its
+    /// similarity group, merging groups that are linked by a common word or stem.
+    pub fn similarity_groups(&self) -> HashMap<String, String> {
+        fn root(parents: &HashMap<String, String>, word: &str) -> String {
+            let mut word = word.to_string();
+            while let Some(parent) = parents.get(&word) {
+                word = parent.clone();
+            }
+            word
+        }
+
+        fn link(parents: &mut HashMap<String, String>, a: &str, b: &str) {
+            let (a, b) = (root(parents, a), root(parents, b));
+            if a != b {
+                parents.insert(a, b);
+            }
+        }
+
+        let mut parents = HashMap::new();
+        for group in &self.groups {
+            let mut members = group.iter().map(|word| word.trim().to_lowercase());
+            if let Some(first) = members.next() {
+                for member in members {
+                    link(&mut parents, &member, &first);
+                }
+            }
+        }
+        // A stem that is itself a word of the pack joins that word's group
+        for (word, stem) in &self.stems {
+            link(
+                &mut parents,
+                &word.trim().to_lowercase(),
+                &stem.trim().to_lowercase(),
+            );
+        }
+
+        self.words
+            .iter()
+            .map(|word| {
+                let word = word.trim().to_lowercase();
+                let key = root(&parents, &word);
+                (word, key)
+            })
+            .collect()
+    }
+
+    /// Maps lowercased words to their difficulty, leaving out normal words.
+    pub fn word_difficulties(&self) -> HashMap<String, WordDifficulty> {
+        self.difficulties
+            .iter()
+            .flat_map(|(difficulty, words)| {
+                words
+                    .iter()
+                    .map(|word| (word.trim().to_lowercase(), *difficulty))
+            })
+            .collect()
+    }
+
+    /// Rejects empty entries, duplicates (after normalizing for the pack's
+    /// language), groups, stems and difficulties naming unknown words, words
+    /// with two difficulties and packs too small to fill a board of
+    /// `min_words` cells with one word per similarity group.
+    pub fn validate(&self, min_words: usize) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Word pack name cannot be empty".to_string());
+        }
+        let mut seen = HashSet::new();
+        for word in &self.words {
+            let normalized = normalize_word(word, &self.language);
+            if normalized.is_empty() {
+                return Err(format!("Word pack {} contains an empty word", self.name));
+            }
+            if !seen.insert(normalized) {
+                return Err(format!("Word pack {} contains {} twice", self.name, word));
+            }
+        }
+        let grouped = self.groups.iter().flatten().chain(self.stems.keys());
+        for word in grouped {
+            if !seen.contains(&normalize_word(word, &self.language)) {
+                return Err(format!(
+                    "Word pack {} groups {}, which is not one of its words",
+                    self.name, word
+                ));
+            }
+        }
+        let mut rated = HashSet::new();
+        for word in self.difficulties.values().flatten() {
+            let normalized = normalize_word(word, &self.language);
+            if !seen.contains(&normalized) {
+                return Err(format!(
+                    "Word pack {} rates {}, which is not one of its words",
+                    self.name, word
+                ));
+            }
+            if !rated.insert(normalized) {
+                return Err(format!(
+                    "Word pack {} gives {} more than one difficulty",
+                    self.name, word
+                ));
+            }
+        }
+        if self.words.len() < min_words {
+            return Err(format!(
+                "Word pack {} has {} words, at least {} are needed",
+                self.name,
+                self.words.len(),
+                min_words
+            ));
+        }
+        let distinct = self
+            .similarity_groups()
+            .into_values()
+            .collect::<HashSet<_>>();
+        if distinct.len() < min_words {
+            return Err(format!(
+                "Word pack {} has {} distinct similarity groups, at least {} are needed",
+                self.name,
+                distinct.len(),
+                min_words
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parses a plain-text pack: one word per line, blank lines are skipped and
+/// `# key: value` header lines set the language and tags. Any number of
+/// `# group: a, b, c` and `# stem: word = stem` lines declare similar words,
+/// and a `# difficulty: easy` line rates every word below it until the next
+/// one. The name defaults to the file name.
+pub fn parse_text_word_pack(name: &str, contents: &str) -> Result<WordPack, String> {
+    let mut name = name.to_string();
+    let mut language = None;
+    let mut tags = Vec::new();
+    let mut words = Vec::new();
+    let mut groups = Vec::new();
+    let mut stems = HashMap::new();
+    let mut difficulties = HashMap::<WordDifficulty, Vec<String>>::new();
+    let mut difficulty = WordDifficulty::Normal;
+    for line in contents.lines() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('#') {
+            let Some((key, value)) = header.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "name" => name = value.to_string(),
+                "language" => {
+                    let Some(code) = Language::from_code(value) else {
+                        return Err(format!("Word pack {name} has unknown language {value}"));
+                    };
+                    language = Some(code);
+                }
+                "tags" => {
+                    tags = value
+                        .split(',')
+                        .map(|tag| tag.trim().to_string())
+                        .filter(|tag| !tag.is_empty())
+                        .collect()
+                }
+                "group" => groups.push(
+                    value
+                        .split(',')
+                        .map(|word| word.trim().to_string())
+                        .filter(|word| !word.is_empty())
+                        .collect(),
+                ),
+                "stem" => {
+                    let Some((word, stem)) = value.split_once('=') else {
+                        return Err(format!("Word pack {name} has a stem line without ="));
+                    };
+                    stems.insert(word.trim().to_string(), stem.trim().to_string());
+                }
+                "difficulty" => {
+                    let Some(rated) = WordDifficulty::from_code(value) else {
+                        return Err(format!("Word pack {name} has unknown difficulty {value}"));
+                    };
+                    difficulty = rated;
+                }
+                _ => {}
+            }
+        } else if !line.is_empty() {
+            words.push(line.to_string());
+            if difficulty != WordDifficulty::Normal {
+                difficulties
+                    .entry(difficulty)
+                    .or_default()
+                    .push(line.to_string());
+            }
+        }
+    }
+    let Some(language) = language else {
+        return Err(format!("Word pack {name} has no language header"));
+    };
+    Ok(WordPack {
+        name,
+        language,
+        tags,
+        words,
+        groups,
+        stems,
+        difficulties,
+    })
+}
+
+pub fn load_word_pack(path: &Path) -> Result<WordPack, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => {
+            serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+        }
+        Some("txt") => parse_text_word_pack(stem, &contents),
+        _ => Err(format!("{}: unsupported word pack format", path.display())),
+    }
+}
+
+/// Loads and validates every `.json` and `.txt` pack in `dir`, sorted by name.
+pub fn load_word_packs(dir: &Path, min_words: usize) -> Result<Vec<WordPack>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut packs = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let supported = matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("json") | Some("txt")
+        );
+        if !path.is_file() || !supported {
+            continue;
+        }
+        let pack = load_word_pack(&path)?;
+        pack.validate(min_words)?;
+        packs.push(pack);
+    }
+
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    if let Some(pair) = packs.windows(2).find(|pair| pair[0].name == pair[1].name) {
+        return Err(format!("Word pack {} is defined twice", pair[0].name));
+    }
+    if packs.is_empty() {
+        return Err(format!("No word packs found in {}", dir.display()));
+    }
+    Ok(packs)
+}
+
+/// Trims a host-supplied word list and checks it for length, duplicates
+/// (normalized like pack words) and profanity in the room's language.
+pub fn validate_custom_words(
+    words: &[String],
+    language: &Language,
+    blocklists: &HashMap<String, Vec<String>>,
+) -> Result<Vec<String>, String> {
+    if words.len() > MAX_CUSTOM_WORDS {
+        return Err(format!(
+            "Custom word list can have at most {MAX_CUSTOM_WORDS} words"
+        ));
+    }
+    let mut seen = HashSet::new();
+    let mut validated = Vec::with_capacity(words.len());
+    for word in words {
+        let word = word.trim();
+        if word.is_empty() {
+            return Err("Custom word list contains an empty word".to_string());
+        }
+        if word.chars().count() > MAX_CUSTOM_WORD_LENGTH {
+            return Err(format!(
+                "Custom word {word} is longer than {MAX_CUSTOM_WORD_LENGTH} characters"
+            ));
+        }
+        if !seen.insert(normalize_word(word, language)) {
+            return Err(format!("Custom word list contains {word} twice"));
+        }
+        if contains_profanity(word, language, blocklists) {
+            return Err(format!("Custom word {word} is not allowed"));
+        }
+        validated.push(word.to_string());
+    }
+    Ok(validated)
+}
+
+/// Checks a user-submitted pack the same way as the built-in ones, plus size,
+/// word length and profanity limits. Returns the trimmed word list.
+pub fn validate_community_word_pack(
+    request: &WordPackRequest,
+    min_words: usize,
+    blocklists: &HashMap<String, Vec<String>>,
+) -> Result<Vec<String>, String> {
+    if request.author.trim().is_empty() {
+        return Err("Word pack author cannot be empty".to_string());
+    }
+    if request.words.len() > MAX_COMMUNITY_WORDS {
+        return Err(format!(
+            "Word pack can have at most {MAX_COMMUNITY_WORDS} words"
+        ));
+    }
+    let words = request
+        .words
+        .iter()
+        .map(|word| word.trim().to_string())
+        .collect::<Vec<_>>();
+    let pack = WordPack {
+        name: request.name.trim().to_string(),
+        language: request.language.clone(),
+        tags: request.tags.clone(),
+        words,
+        groups: Vec::new(),
+        stems: HashMap::new(),
+        difficulties: HashMap::new(),
+    };
+    pack.validate(min_words)?;
+
+    if contains_profanity(&pack.name, &pack.language, blocklists) {
+        return Err(format!("Word pack name {} is not allowed", pack.name));
+    }
+    if let Some(tag) = pack
+        .tags
+        .iter()
+        .find(|tag| contains_profanity(tag, &pack.language, blocklists))
+    {
+        return Err(format!("Tag {tag} is not allowed"));
+    }
+    for word in &pack.words {
+        if word.chars().count() > MAX_CUSTOM_WORD_LENGTH {
+            return Err(format!(
+                "Word {word} is longer than {MAX_CUSTOM_WORD_LENGTH} characters"
+            ));
+        }
+        if contains_profanity(word, &pack.language, blocklists) {
+            return Err(format!("Word {word} is not allowed"));
+        }
+    }
+    Ok(pack.words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::{choose_board_words, seeded_rng, WordPool, MAX_BOARD_SIDE},
+        types::Difficulty,
+    };
+
+    fn pack(words: &[&str]) -> WordPack {
+        WordPack {
+            name: "test".to_string(),
+            language: Language::English,
+            tags: Vec::new(),
+            words: words.iter().map(|word| word.to_string()).collect(),
+            groups: Vec::new(),
+            stems: HashMap::new(),
+            difficulties: HashMap::new(),
+        }
+    }
+
+    fn numbered(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("w{i:02}")).collect()
+    }
+
+    #[test]
+    fn groups_linked_by_a_word_or_stem_merge() {
+        let mut pack = pack(&["Run", "runner", "running", "sprint", "dog"]);
+        pack.groups = vec![vec!["run".to_string(), "Runner".to_string()]];
+        pack.stems = HashMap::from([
+            ("running".to_string(), "run".to_string()),
+            ("sprint".to_string(), "running".to_string()),
+        ]);
+        let groups = pack.similarity_groups();
+        let run = &groups["run"];
+        assert!(["runner", "running", "sprint"]
+            .iter()
+            .all(|word| &groups[*word] == run));
+        assert_ne!(&groups["dog"], run);
+    }
+
+    #[test]
+    fn boards_take_one_word_per_similarity_group() {
+        let mut pack = pack(&[]);
+        pack.words = numbered(30);
+        // Five groups of three words leave 30 words with 20 distinct picks
+        pack.groups = pack.words[..15]
+            .chunks(3)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+        let groups = pack.similarity_groups();
+        let pool = WordPool {
+            pack_words: &pack.words,
+            custom_words: &[],
+            custom_ratio: 0,
+            groups: &groups,
+            difficulties: &HashMap::new(),
+            difficulty: &Difficulty::Normal,
+            recent_words: &HashSet::new(),
+        };
+        for seed in 0..20 {
+            let chosen = choose_board_words(&pool, 20, &mut seeded_rng(seed));
+            assert_eq!(chosen.len(), 20);
+            let picked_groups = chosen
+                .iter()
+                .map(|word| &groups[word])
+                .collect::<HashSet<_>>();
+            assert_eq!(picked_groups.len(), chosen.len());
+        }
+        assert!(pack.validate(20).is_ok());
+        assert!(pack.validate(21).is_err());
+    }
+
+    #[test]
+    fn groups_must_name_words_of_the_pack() {
+        let mut grouped = pack(&["run", "runner", "dog"]);
+        grouped.groups = vec![vec!["run".to_string(), "sprinter".to_string()]];
+        let error = grouped.validate(1).unwrap_err();
+        assert!(error.contains("sprinter"), "{error}");
+
+        let mut stemmed = pack(&["run", "runner", "dog"]);
+        stemmed.stems = HashMap::from([("runs".to_string(), "run".to_string())]);
+        assert!(stemmed.validate(1).is_err());
+    }
+
+    #[test]
+    fn bundled_packs_rate_easy_and_hard_words() {
+        let packs = load_word_packs(Path::new(DEFAULT_WORD_PACKS_DIR), 0).unwrap();
+        for pack in &packs {
+            for difficulty in [WordDifficulty::Easy, WordDifficulty::Hard] {
+                let rated = pack.difficulties.get(&difficulty).map_or(0, Vec::len);
+                assert!(
+                    rated >= 25,
+                    "{} rates {rated} {difficulty:?} words",
+                    pack.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn text_packs_skip_blank_lines_and_read_headers() {
+        let contents = "# language: de\n# tags: Tiere, , Natur\n\nBär\n   \n# difficulty: hard\n  Igel  \n# difficulty: normal\nFuchs\n";
+        let pack = parse_text_word_pack("tiere", contents).unwrap();
+        assert_eq!(pack.name, "tiere");
+        assert_eq!(pack.language, Language::German);
+        assert_eq!(pack.tags, ["Tiere", "Natur"]);
+        assert_eq!(pack.words, ["Bär", "Igel", "Fuchs"]);
+        assert_eq!(
+            pack.word_difficulties(),
+            HashMap::from([("igel".to_string(), WordDifficulty::Hard)])
+        );
+
+        assert!(parse_text_word_pack("tiere", "Bär\nIgel\n").is_err());
+    }
+
+    #[test]
+    fn packs_reject_duplicates_after_normalizing() {
+        let mut german = pack(&["Straße", " STRASSE", "Weg"]);
+        german.language = Language::German;
+        let error = german.validate(1).unwrap_err();
+        assert!(error.contains("twice"), "{error}");
+        assert!(pack(&["Dog", "dog "]).validate(1).is_err());
+        // Polish words that only differ in diacritics are different words
+        let mut polish = pack(&["kat", "kąt"]);
+        polish.language = Language::Polish;
+        assert!(polish.validate(1).is_ok());
+    }
+
+    #[test]
+    fn packs_reject_blank_words() {
+        assert!(pack(&["dog", "  ", "cat"]).validate(1).is_err());
+        assert!(pack(&["dog", ""]).validate(1).is_err());
+    }
+
+    #[test]
+    fn packs_need_enough_words_for_a_board() {
+        let mut small = pack(&[]);
+        small.words = numbered(24);
+        let error = small.validate(25).unwrap_err();
+        assert!(error.contains("24 words"), "{error}");
+        small.words = numbered(25);
+        assert!(small.validate(25).is_ok());
+    }
+
+    #[test]
+    fn bundled_packs_load_and_fill_the_largest_board() {
+        let min_words = (MAX_BOARD_SIDE * MAX_BOARD_SIDE) as usize;
+        let packs = load_word_packs(Path::new(DEFAULT_WORD_PACKS_DIR), min_words).unwrap();
+        assert!(packs.iter().any(|pack| pack.name == DEFAULT_WORD_PACK));
+        for language in [Language::Polish, Language::English, Language::German] {
+            assert!(packs.iter().any(|pack| pack.language == language));
+        }
+        for pack in &packs {
+            assert!(pack.validate(min_words).is_ok(), "{}", pack.name);
+        }
+    }
+
+    #[test]
+    fn custom_words_are_deduplicated_like_pack_words() {
+        let blocklists = HashMap::new();
+        let words = ["Straße".to_string(), "STRASSE".to_string()];
+        assert!(validate_custom_words(&words, &Language::German, &blocklists).is_err());
+        let words = [" Dog".to_string(), "dog".to_string()];
+        assert!(validate_custom_words(&words, &Language::English, &blocklists).is_err());
+        let words = [" Dog ".to_string(), "cat".to_string()];
+        assert_eq!(
+            validate_custom_words(&words, &Language::English, &blocklists).unwrap(),
+            ["Dog", "cat"]
+        );
+    }
+}
@@ -1,432 +0,0 @@
-pub const WORDS: [&str; 430] = [
-    "agent",
-    "księżyc",
-    "gwiazda",
-    "morze",
-    "słońce",
-    "pies",
-    "kot",
-    "dom",
-    "samochód",
-    "drzewo",
-    "książka",
-    "miasto",
-    "wieża",
-    "statek",
-    "szkoła",
-    "komputer",
-    "telefon",
-    "ogród",
-    "żółw",
-    "fotel",
-    "butelka",
-    "zegar",
-    "chleb",
-    "sok",
-    "pokój",
-    "park",
-    "sklep",
-    "muzyka",
-    "ryba",
-    "ptak",
-    "las",
-    "góra",
-    "dolina",
-    "jezioro",
-    "rzeka",
-    "most",
-    "zamek",
-    "film",
-    "spektakl",
-    "taniec",
-    "piłka",
-    "rower",
-    "świeca",
-    "jajko",
-    "ser",
-    "kanapka",
-    "lampa",
-    "filiżanka",
-    "talizman",
-    "łódka",
-    "czapka",
-    "śmiech",
-    "kawa",
-    "herbata",
-    "cukier",
-    "piesek",
-    "kotka",
-    "rycerz",
-    "król",
-    "królowa",
-    "książę",
-    "księżniczka",
-    "zamek",
-    "smok",
-    "wojna",
-    "pokój",
-    "miłość",
-    "przyjaźń",
-    "złoto",
-    "srebro",
-    "diament",
-    "perła",
-    "kwiat",
-    "drzewo",
-    "krzew",
-    "owoc",
-    "warzywo",
-    "chmura",
-    "deszcz",
-    "słońce",
-    "śnieg",
-    "burza",
-    "wiatr",
-    "fala",
-    "plaża",
-    "morze",
-    "rzeka",
-    "jezioro",
-    "ocean",
-    "latarnia",
-    "port",
-    "statua",
-    "pomnik",
-    "katedra",
-    "pałac",
-    "wieża",
-    "młyn",
-    "szpital",
-    "kino",
-    "teatr",
-    "biblioteka",
-    "muzeum",
-    "galeria",
-    "sklep",
-    "restauracja",
-    "kawiarnia",
-    "bar",
-    "targ",
-    "bazylika",
-    "synagoga",
-    "meczet",
-    "świątynia",
-    "las",
-    "dżungla",
-    "sawanna",
-    "pustynia",
-    "wyspa",
-    "kontynent",
-    "kraj",
-    "miasto",
-    "wieś",
-    "osada",
-    "plac",
-    "ulica",
-    "aleja",
-    "droga",
-    "autostrada",
-    "tor",
-    "peron",
-    "stacja",
-    "lotnisko",
-    "port",
-    "przystań",
-    "dworzec",
-    "metro",
-    "tramwaj",
-    "autobus",
-    "taxi",
-    "pociąg",
-    "samolot",
-    "rakieta",
-    "statek",
-    "łódź",
-    "kajak",
-    "jacht",
-    "motorówka",
-    "żaglówka",
-    "karabin",
-    "pistolet",
-    "miecz",
-    "szabla",
-    "kusza",
-    "łuk",
-    "strzała",
-    "tarcza",
-    "pancerz",
-    "zbroja",
-    "hełm",
-    "strój",
-    "kostium",
-    "mundur",
-    "kurtka",
-    "płaszcz",
-    "kapelusz",
-    "beret",
-    "cylinder",
-    "czapka",
-    "szalik",
-    "rękawiczki",
-    "buty",
-    "kalosze",
-    "klapki",
-    "trampki",
-    "adidasy",
-    "sandaly",
-    "mokasyny",
-    "pantofle",
-    "łóżko",
-    "sofa",
-    "fotel",
-    "krzesło",
-    "stół",
-    "biurko",
-    "regał",
-    "szafa",
-    "komoda",
-    "lampa",
-    "lustro",
-    "dywan",
-    "firanka",
-    "zasłona",
-    "koc",
-    "poduszka",
-    "kołdra",
-    "prześcieradło",
-    "ręcznik",
-    "szczotka",
-    "grzebień",
-    "szampon",
-    "mydło",
-    "pasta",
-    "szczoteczka",
-    "maszynka",
-    "golarka",
-    "lusterko",
-    "suszarka",
-    "żelazko",
-    "pralka",
-    "zmywarka",
-    "lodówka",
-    "kuchenka",
-    "mikrofalówka",
-    "toster",
-    "czajnik",
-    "blender",
-    "mikserek",
-    "garnek",
-    "patelnia",
-    "nóż",
-    "widelec",
-    "łyżka",
-    "kieliszek",
-    "szklanka",
-    "kubek",
-    "talerz",
-    "miska",
-    "wazon",
-    "butelka",
-    "puszka",
-    "słoik",
-    "korek",
-    "kapsel",
-    "nakrętka",
-    "kalendarz",
-    "zegar",
-    "budzik",
-    "klepsydra",
-    "termometr",
-    "barometr",
-    "kompas",
-    "mapa",
-    "atlas",
-    "globus",
-    "notes",
-    "długopis",
-    "ołówek",
-    "gumka",
-    "linijka",
-    "ekierka",
-    "kalkulator",
-    "plecak",
-    "torba",
-    "walizka",
-    "parasol",
-    "lornetka",
-    "luneta",
-    "mikroskop",
-    "teleskop",
-    "kamera",
-    "aparat",
-    "telewizor",
-    "radio",
-    "komputer",
-    "laptop",
-    "tablet",
-    "smartfon",
-    "drukarka",
-    "skaner",
-    "modem",
-    "router",
-    "kabel",
-    "przedłużacz",
-    "gniazdo",
-    "wtyczka",
-    "wtyczka",
-    "głośnik",
-    "słuchawki",
-    "mikrofon",
-    "klawiatura",
-    "mysz",
-    "monitor",
-    "ekran",
-    "projektor",
-    "pilot",
-    "bateria",
-    "akumulator",
-    "ładowarka",
-    "adapter",
-    "konwerter",
-    "pamięć",
-    "dysk",
-    "pendrive",
-    "karta",
-    "chip",
-    "procesor",
-    "serwer",
-    "chłodzenie",
-    "wentylator",
-    "obudowa",
-    "zasilacz",
-    "płyta",
-    "gra",
-    "program",
-    "aplikacja",
-    "system",
-    "sieć",
-    "internet",
-    "przeglądarka",
-    "strona",
-    "serwis",
-    "apka",
-    "konto",
-    "profil",
-    "konto",
-    "login",
-    "hasło",
-    "kod",
-    "qr",
-    "technologia",
-    "innowacja",
-    "wiedza",
-    "nauka",
-    "badanie",
-    "eksperyment",
-    "teoria",
-    "praktyka",
-    "metoda",
-    "technika",
-    "analiza",
-    "synteza",
-    "interpretacja",
-    "model",
-    "symulacja",
-    "algorytm",
-    "programowanie",
-    "kodowanie",
-    "debugowanie",
-    "testowanie",
-    "implementacja",
-    "system",
-    "architektura",
-    "struktura",
-    "platforma",
-    "usługa",
-    "produkt",
-    "projekt",
-    "plan",
-    "harmonogram",
-    "strategia",
-    "taktyka",
-    "operacja",
-    "kampania",
-    "misja",
-    "zadanie",
-    "cel",
-    "wynik",
-    "osiągnięcie",
-    "sukces",
-    "porażka",
-    "ryzyko",
-    "zagrożenie",
-    "wyzwanie",
-    "problem",
-    "rozwiązanie",
-    "decyzja",
-    "opcja",
-    "wybór",
-    "priorytet",
-    "kryterium",
-    "norma",
-    "standard",
-    "reguła",
-    "zasada",
-    "protokół",
-    "procedura",
-    "instrukcja",
-    "przewodnik",
-    "manual",
-    "dokument",
-    "raport",
-    "analiza",
-    "ocena",
-    "recenzja",
-    "komentarz",
-    "opinie",
-    "recenzja",
-    "komentarz",
-    "pytanie",
-    "odpowiedź",
-    "dyskusja",
-    "debata",
-    "konsultacja",
-    "spotkanie",
-    "konferencja",
-    "seminarium",
-    "warsztat",
-    "trening",
-    "szkolenie",
-    "kurs",
-    "lekcja",
-    "zajęcia",
-    "edukacja",
-    "nauka",
-    "uczeń",
-    "student",
-    "nauczyciel",
-    "wykładowca",
-    "profesor",
-    "doktor",
-    "specjalista",
-    "ekspert",
-    "inżynier",
-    "architekt",
-    "konstruktor",
-    "projektant",
-    "menedżer",
-    "lider",
-    "kierownik",
-    "dyrektor",
-    "prezes",
-    "szef",
-    "przewodniczący",
-    "członek",
-    "zespół",
-    "grupa",
-    "organizacja",
-    "firma",
-    "przedsiębiorstwo",
-    "korporacja",
-    "instytucja",
-];